@@ -6,9 +6,13 @@
 //
 // 3. EXTRA: try changing the type from i32 into String everywhere; does your program still compile? What changes are necessary?
 
-/// Merge two array slices (that have to be sorted) into a vector
-fn merge(a: &[String], b: &[String]) -> Vec<String> {
-    let mut dest = Vec::new();
+use std::cmp::Ordering;
+
+/// Merge two array slices (that have to be sorted) into a vector.
+/// On equal elements, the one from `a` goes first, which keeps the merge stable
+fn merge<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    // Two slices of non-zero-sized elements can't overflow this, but zero-sized ones can
+    let mut dest = Vec::with_capacity(a.len().checked_add(b.len()).expect("merge length overflow"));
 
     let mut a_idx = 0;
     let mut b_idx = 0;
@@ -23,35 +27,304 @@ fn merge(a: &[String], b: &[String]) -> Vec<String> {
         }
     }
 
-    for &elem in a[a_idx..].iter() {
-        dest.push(elem)
+    for elem in a[a_idx..].iter() {
+        dest.push(elem.clone())
+    }
+    for elem in b[b_idx..].iter() {
+        dest.push(elem.clone())
+    }
+
+    dest
+}
+
+/// Like `merge`, but for `Copy` elements the leftover tail of `a` or `b` is
+/// copied over in bulk rather than element by element
+#[allow(dead_code)]
+fn merge_copy<T: Ord + Copy>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut dest = Vec::with_capacity(a.len().checked_add(b.len()).expect("merge length overflow"));
+
+    let mut a_idx = 0;
+    let mut b_idx = 0;
+
+    while a_idx < a.len() && b_idx < b.len() {
+        if a[a_idx] <= b[b_idx] {
+            dest.push(a[a_idx]);
+            a_idx += 1
+        } else {
+            dest.push(b[b_idx]);
+            b_idx += 1
+        }
+    }
+
+    dest.extend_from_slice(&a[a_idx..]);
+    dest.extend_from_slice(&b[b_idx..]);
+
+    dest
+}
+
+/// Take an array slice, and sort into a freshly constructed vector using the above function.
+/// The sort is stable: equal elements keep their relative order
+#[allow(dead_code)]
+fn merge_sort<T: Ord + Clone>(data: &[T]) -> Vec<T> {
+    merge_sort_with_progress(data, &mut |_| {})
+}
+
+/// Like `merge_sort`, but puts the result into `dst` (replacing its contents),
+/// so the allocation of `dst` can be reused when sorting many inputs.
+/// `dst` only needs room for `src.len()` elements: the runs are merged back and forth
+/// between `dst` and a single scratch copy of `src`
+#[allow(dead_code)]
+fn merge_sort_into<T: Ord + Clone>(src: &[T], dst: &mut Vec<T>) {
+    let len = src.len();
+    dst.clear();
+    dst.reserve(len);
+    dst.extend_from_slice(src);
+    let mut scratch = src.to_vec();
+
+    // Merge runs of `width` elements from one buffer into the other
+    let mut sorted_in_dst = true;
+    let mut width = 1;
+    while width < len {
+        let (from, to) = if sorted_in_dst { (&*dst, &mut scratch) } else { (&scratch, &mut *dst) };
+        for start in (0..len).step_by(2 * width) {
+            let mid = (start + width).min(len);
+            let end = (start + 2 * width).min(len);
+            merge_into_slice(&from[start..mid], &from[mid..end], &mut to[start..end]);
+        }
+        sorted_in_dst = !sorted_in_dst;
+        width *= 2;
+    }
+
+    if !sorted_in_dst {
+        dst.swap_with_slice(&mut scratch);
+    }
+}
+
+/// Merge two array slices (that have to be sorted) into `dest`, which must be exactly as
+/// long as both of them together. Like `merge`, this is stable
+fn merge_into_slice<T: Ord + Clone>(a: &[T], b: &[T], dest: &mut [T]) {
+    let mut a_idx = 0;
+    let mut b_idx = 0;
+
+    for slot in dest.iter_mut() {
+        if b_idx >= b.len() || (a_idx < a.len() && a[a_idx] <= b[b_idx]) {
+            slot.clone_from(&a[a_idx]);
+            a_idx += 1
+        } else {
+            slot.clone_from(&b[b_idx]);
+            b_idx += 1
+        }
+    }
+}
+
+/// Like `merge_sort`, but calls `on_merge` after each merge step with the number of
+/// elements merged so far, counted from the front of `data`. The numbers never decrease,
+/// and the last call, after the final merge, reports `data.len()`. With fewer than 2 elements
+/// there is nothing to merge, so `on_merge` is never called
+fn merge_sort_with_progress<T, F>(data: &[T], on_merge: &mut F) -> Vec<T>
+where
+    T: Ord + Clone,
+    F: FnMut(usize),
+{
+    merge_sort_progress(data, 0, on_merge)
+}
+
+/// Sort `data`, which starts at `offset` in the slice being sorted. The left half is
+/// sorted before the right one, so once `data` is merged, so is everything before it
+fn merge_sort_progress<T, F>(data: &[T], offset: usize, on_merge: &mut F) -> Vec<T>
+where
+    T: Ord + Clone,
+    F: FnMut(usize),
+{
+    let dat_len = data.len();
+
+    if dat_len > 1 {
+        let mid = dat_len / 2;
+        let left = merge_sort_progress(&data[0..mid], offset, on_merge);
+        let right = merge_sort_progress(&data[mid..dat_len], offset + mid, on_merge);
+        let merged = merge(left.as_slice(), right.as_slice());
+        on_merge(offset + dat_len);
+        merged
+    } else {
+        data.to_vec()
+    }
+}
+
+/// Like `merge_sort`, but instead of splitting down to single elements it splits `data`
+/// into its ascending and strictly descending runs (the latter reversed) and merges those,
+/// so partially sorted inputs need fewer merges. The sort is stable as well
+#[allow(dead_code)]
+fn merge_sort_natural<T: Ord + Clone>(data: &[T]) -> Vec<T> {
+    let dat_len = data.len();
+    let mut runs: Vec<Vec<T>> = Vec::new();
+
+    let mut start = 0;
+    while start < dat_len {
+        let mut end = start + 1;
+        // Descending runs must be strict, as reversing equal elements would break stability
+        if end < dat_len && data[end] < data[end - 1] {
+            while end < dat_len && data[end] < data[end - 1] {
+                end += 1
+            }
+            runs.push(data[start..end].iter().rev().cloned().collect());
+        } else {
+            while end < dat_len && data[end - 1] <= data[end] {
+                end += 1
+            }
+            runs.push(data[start..end].to_vec());
+        }
+        start = end;
+    }
+
+    // Merge neighbouring runs until one is left, so equal elements keep their order
+    while runs.len() > 1 {
+        let mut merged = Vec::with_capacity(runs.len().div_ceil(2));
+        let mut runs_iter = runs.into_iter();
+        while let Some(a) = runs_iter.next() {
+            match runs_iter.next() {
+                Some(b) => merged.push(merge(a.as_slice(), b.as_slice())),
+                None => merged.push(a),
+            }
+        }
+        runs = merged;
     }
-    for &elem in b[b_idx..].iter() {
-        dest.push(elem)
+
+    runs.pop().unwrap_or_default()
+}
+
+/// Like `merge_sort`, but orders the elements using `compare`,
+/// analogous to `slice::sort_by`
+fn merge_sort_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(data: &[T], compare: &mut F) -> Vec<T> {
+    let dat_len = data.len();
+
+    if dat_len > 1 {
+        let mid = dat_len / 2;
+        let left = merge_sort_by(&data[0..mid], compare);
+        let right = merge_sort_by(&data[mid..dat_len], compare);
+        merge_by(left.as_slice(), right.as_slice(), compare)
+    } else {
+        data.to_vec()
     }
+}
+
+/// Merge two array slices (that have to be sorted according to `compare`) into a vector
+fn merge_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(a: &[T], b: &[T], compare: &mut F) -> Vec<T> {
+    let mut dest = Vec::with_capacity(a.len().checked_add(b.len()).expect("merge length overflow"));
+
+    let mut a_idx = 0;
+    let mut b_idx = 0;
+
+    while a_idx < a.len() && b_idx < b.len() {
+        if compare(&a[a_idx], &b[b_idx]) != Ordering::Greater {
+            dest.push(a[a_idx].clone());
+            a_idx += 1
+        } else {
+            dest.push(b[b_idx].clone());
+            b_idx += 1
+        }
+    }
+
+    dest.extend_from_slice(&a[a_idx..]);
+    dest.extend_from_slice(&b[b_idx..]);
 
     dest
 }
 
-/// Take an array slice, and sort into a freshly constructed vector using the above function
-fn merge_sort(data: &[String]) -> Vec<String> {
+/// Like `merge_sort`, but orders the elements by the key computed by `key`,
+/// analogous to `slice::sort_by_key`
+#[allow(dead_code)]
+fn merge_sort_by_key<T: Clone, K: Ord, F: FnMut(&T) -> K>(data: &[T], key: &mut F) -> Vec<T> {
     let dat_len = data.len();
 
     if dat_len > 1 {
         let mid = dat_len / 2;
-        let left = merge_sort(&data[0..mid]);
-        let right = merge_sort(&data[mid..dat_len]);
-        merge(left.as_slice(), right.as_slice())
+        let left = merge_sort_by_key(&data[0..mid], key);
+        let right = merge_sort_by_key(&data[mid..dat_len], key);
+        merge_by_key(left.as_slice(), right.as_slice(), key)
     } else {
         data.to_vec()
     }
 }
 
+/// Merge two array slices (that have to be sorted by `key`) into a vector
+fn merge_by_key<T: Clone, K: Ord, F: FnMut(&T) -> K>(a: &[T], b: &[T], key: &mut F) -> Vec<T> {
+    let mut dest = Vec::with_capacity(a.len().checked_add(b.len()).expect("merge length overflow"));
+
+    let mut a_idx = 0;
+    let mut b_idx = 0;
+
+    while a_idx < a.len() && b_idx < b.len() {
+        if key(&a[a_idx]) <= key(&b[b_idx]) {
+            dest.push(a[a_idx].clone());
+            a_idx += 1
+        } else {
+            dest.push(b[b_idx].clone());
+            b_idx += 1
+        }
+    }
+
+    dest.extend_from_slice(&a[a_idx..]);
+    dest.extend_from_slice(&b[b_idx..]);
+
+    dest
+}
+
+/// Compute the permutation of indices that would sort `data`, without cloning any element:
+/// `data[indices[0]]` is the smallest element, `data[indices[1]]` the next, and so on.
+/// Equal elements keep their relative order
+#[allow(dead_code)]
+fn merge_sort_indices<T: Ord>(data: &[T]) -> Vec<usize> {
+    let indices: Vec<usize> = (0..data.len()).collect();
+    merge_sort_by(&indices, &mut |&a, &b| data[a].cmp(&data[b]))
+}
+
+/// Sort a vector by moving its elements, so that no `Clone` is needed.
+/// Elements are moved back and forth between the input and a single scratch buffer.
+#[allow(dead_code)]
+fn merge_sort_in_place<T: Ord>(data: Vec<T>) -> Vec<T> {
+    let mut data: Vec<Option<T>> = data.into_iter().map(Some).collect();
+    let mut scratch: Vec<Option<T>> = std::iter::repeat_with(|| None).take(data.len()).collect();
+
+    merge_sort_options(&mut data, &mut scratch);
+
+    data.into_iter().flatten().collect()
+}
+
+/// Sort `data` in place, using `scratch` (which must be as long as `data` and contain
+/// only `None`s) to hold the two sorted halves while merging them back into `data`
+fn merge_sort_options<T: Ord>(data: &mut [Option<T>], scratch: &mut [Option<T>]) {
+    let dat_len = data.len();
+
+    if dat_len <= 1 {
+        return;
+    }
+
+    let mid = dat_len / 2;
+    merge_sort_options(&mut data[0..mid], &mut scratch[0..mid]);
+    merge_sort_options(&mut data[mid..dat_len], &mut scratch[mid..dat_len]);
+
+    scratch.swap_with_slice(data);
+    let (a, b) = scratch.split_at_mut(mid);
+
+    let mut a_idx = 0;
+    let mut b_idx = 0;
+
+    for slot in data.iter_mut() {
+        if b_idx >= b.len() || (a_idx < a.len() && a[a_idx] <= b[b_idx]) {
+            *slot = a[a_idx].take();
+            a_idx += 1
+        } else {
+            *slot = b[b_idx].take();
+            b_idx += 1
+        }
+    }
+}
+
 /// Read a bunch of numbers from standard input into a Vec<i32>.
 fn read_numbers() -> Vec<String> {
     use std::io;
     let mut result = Vec::new();
-    for line in io::stdin().lines().flatten() {
+    for line in io::stdin().lines().map_while(Result::ok) {
         for word in line.split_whitespace() {
             result.push(word.parse().unwrap())
         }
@@ -60,12 +333,24 @@ fn read_numbers() -> Vec<String> {
     result
 }
 
+/// Pick the order to sort in from the command line arguments:
+/// descending if `--reverse` or `-r` is passed, ascending otherwise
+fn comparator_from_args<T: Ord, I: IntoIterator<Item = String>>(args: I) -> fn(&T, &T) -> Ordering {
+    if args.into_iter().any(|arg| arg == "--reverse" || arg == "-r") {
+        |a, b| b.cmp(a)
+    } else {
+        T::cmp
+    }
+}
+
 fn main() {
+    let mut compare = comparator_from_args(std::env::args().skip(1));
+
     let input = read_numbers();
     println!("Data to be sorted:");
     println!("{input:?}");
 
-    let sorted_input = merge_sort(&input);
+    let sorted_input = merge_sort_by(&input, &mut compare);
     println!("Sorted data:");
     println!("{sorted_input:?}");
 }
@@ -77,9 +362,255 @@ mod test {
 
     #[test]
     fn test_sort() {
-        assert_eq!(merge_sort(&[]), vec![]);
+        assert_eq!(merge_sort::<String>(&[]), Vec::<String>::new());
         assert_eq!(merge_sort(&[String::from("a")]), vec![String::from("a")]);
         assert_eq!(merge_sort(&[String::from("a"),String::from("b"), String::from("c")]), vec![String::from("a"),String::from("b"), String::from("c")]);
         assert_eq!(merge_sort(&[String::from("c"),String::from("b"), String::from("a")]), vec![String::from("a"),String::from("b"), String::from("c")]);
     }
+
+    #[test]
+    fn test_merge() {
+        let a = [1, 3, 3, 8];
+        let b = [2, 3, 9];
+        let merged = merge(&a, &b);
+
+        assert_eq!(merged, vec![1, 2, 3, 3, 3, 8, 9]);
+        assert_eq!(merged, merge_by_key(&a, &b, &mut |&n| n));
+        assert_eq!(merged.capacity(), a.len() + b.len());
+
+        assert_eq!(merge::<u32>(&[], &[]), vec![]);
+        assert_eq!(merge(&[1], &[]), vec![1]);
+    }
+
+    #[test]
+    fn test_merge_copy() {
+        let inputs: [(&[i32], &[i32]); 5] = [
+            (&[1, 3, 3, 8], &[2, 3, 9]),
+            (&[], &[1, 2]),
+            (&[1, 2], &[]),
+            (&[5, 6, 7], &[1, 2]),
+            (&[-4, 0, 10, 20, 30], &[-5, 15]),
+        ];
+        for (a, b) in inputs {
+            let merged = merge_copy(a, b);
+            assert_eq!(merged, merge(a, b));
+            assert_eq!(merged.capacity(), a.len() + b.len());
+        }
+    }
+
+    #[test]
+    fn test_sort_into() {
+        let mut dst = vec![42; 3];
+
+        merge_sort_into(&[5, 1, 4, 2, 3], &mut dst);
+        assert_eq!(dst, vec![1, 2, 3, 4, 5]);
+
+        let capacity = dst.capacity();
+        let ptr = dst.as_ptr();
+        assert!(capacity >= 5);
+        merge_sort_into(&[9, 7, 8], &mut dst);
+        assert_eq!(dst, vec![7, 8, 9]);
+        assert_eq!(dst.capacity(), capacity);
+        assert_eq!(dst.as_ptr(), ptr);
+
+        merge_sort_into(&[3, 3, 1, 5, 2], &mut dst);
+        assert_eq!(dst, vec![1, 2, 3, 3, 5]);
+        assert_eq!(dst.capacity(), capacity);
+        assert_eq!(dst.as_ptr(), ptr);
+
+        merge_sort_into(&[], &mut dst);
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    fn test_sort_with_progress() {
+        for len in [2, 3, 10, 1000] {
+            let data: Vec<u32> = (0..len).rev().collect();
+            let mut reports = Vec::new();
+
+            let sorted = merge_sort_with_progress(&data, &mut |merged| reports.push(merged));
+            assert_eq!(sorted, (0..len).collect::<Vec<_>>());
+
+            // Splitting down to single elements takes `len - 1` merges, and each is reported,
+            // so the last report comes after the final merge
+            assert_eq!(reports.len(), data.len() - 1);
+            assert!(reports.windows(2).all(|w| w[0] <= w[1]), "{reports:?}");
+            let mut prev = 0;
+            let increments: usize = reports.iter().map(|&merged| merged - std::mem::replace(&mut prev, merged)).sum();
+            assert_eq!(increments, data.len());
+            assert_eq!(reports.last(), Some(&data.len()));
+        }
+
+        let mut calls = 0;
+        merge_sort_with_progress(&[1], &mut |_| calls += 1);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn test_sort_by_key() {
+        let people = [("Alice", 32), ("Bob", 25), ("Carol", 41), ("Dave", 25)];
+        let sorted = merge_sort_by_key(&people, &mut |&(_, age)| age);
+        assert_eq!(sorted, vec![("Bob", 25), ("Dave", 25), ("Alice", 32), ("Carol", 41)]);
+    }
+
+    #[test]
+    fn test_sort_indices() {
+        let data = [String::from("c"), String::from("a"), String::from("b")];
+        let indices = merge_sort_indices(&data);
+        assert_eq!(indices, vec![1, 2, 0]);
+        assert_eq!(indices.iter().map(|&i| data[i].as_str()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+
+        assert_eq!(merge_sort_indices(&[2, 1, 2, 1]), vec![1, 3, 0, 2]);
+        assert!(merge_sort_indices::<i32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_reverse_flag() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        let mut compare = comparator_from_args(args(&["-r"]));
+        assert_eq!(merge_sort_by(&[2, 3, 1], &mut compare), vec![3, 2, 1]);
+        let mut compare = comparator_from_args(args(&["--reverse"]));
+        assert_eq!(merge_sort_by(&["a", "c", "b"], &mut compare), vec!["c", "b", "a"]);
+
+        let mut compare = comparator_from_args(args(&[]));
+        assert_eq!(merge_sort_by(&[2, 3, 1], &mut compare), vec![1, 2, 3]);
+        let mut compare = comparator_from_args(args(&["-x"]));
+        assert_eq!(merge_sort_by(&[2, 3, 1], &mut compare), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sort_stable() {
+        /// An element that is ordered by its `key` only
+        #[derive(Debug, Clone)]
+        struct Keyed {
+            key: u32,
+            index: usize,
+        }
+
+        impl PartialEq for Keyed {
+            fn eq(&self, other: &Self) -> bool {
+                self.key == other.key
+            }
+        }
+
+        impl Eq for Keyed {}
+
+        impl PartialOrd for Keyed {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Keyed {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.key.cmp(&other.key)
+            }
+        }
+
+        let keys = [3, 1, 2, 1, 3, 2, 1, 3, 2, 1];
+        let data: Vec<Keyed> = keys.iter().enumerate().map(|(index, &key)| Keyed { key, index }).collect();
+
+        for sorted in [merge_sort(&data), merge_sort_natural(&data)] {
+            for pair in sorted.windows(2) {
+                assert!(pair[0].key <= pair[1].key);
+                if pair[0].key == pair[1].key {
+                    assert!(pair[0].index < pair[1].index, "{pair:?} is not in original order");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_in_place() {
+        assert_eq!(merge_sort_in_place(Vec::<String>::new()), Vec::<String>::new());
+        assert_eq!(merge_sort_in_place(vec![String::from("a")]), vec![String::from("a")]);
+        assert_eq!(
+            merge_sort_in_place(vec![String::from("d"), String::from("b"), String::from("c"), String::from("a"), String::from("b")]),
+            vec![String::from("a"), String::from("b"), String::from("b"), String::from("c"), String::from("d")]
+        );
+
+        // `NoClone` does not implement `Clone`, so this only compiles if no element is ever cloned
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct NoClone(String);
+
+        let sorted = merge_sort_in_place(vec![NoClone(String::from("c")), NoClone(String::from("a")), NoClone(String::from("b"))]);
+        assert_eq!(sorted, vec![NoClone(String::from("a")), NoClone(String::from("b")), NoClone(String::from("c"))]);
+    }
+}
+
+// Compare `merge_sort` against the standard library sort on pseudo-random inputs
+#[cfg(test)]
+mod test_against_std {
+    use super::*;
+
+    /// A small xorshift PRNG, so the inputs are deterministic without any dependencies
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Generate `len` numbers in the range `0..max`
+        fn vec(&mut self, len: usize, max: u64) -> Vec<i32> {
+            (0..len).map(|_| (self.next() % max) as i32).collect()
+        }
+    }
+
+    const SIZES: [usize; 9] = [0, 1, 2, 3, 7, 16, 101, 1000, 10_000];
+
+    fn assert_sorts_like_std(data: &[i32]) {
+        let mut expected = data.to_vec();
+        expected.sort();
+        assert_eq!(merge_sort(data), expected, "failed to sort {} elements", data.len());
+        assert_eq!(merge_sort_natural(data), expected, "failed to sort {} elements naturally", data.len());
+
+        let mut dst = Vec::new();
+        merge_sort_into(data, &mut dst);
+        assert_eq!(dst, expected, "failed to sort {} elements into a buffer", data.len());
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+        for len in SIZES {
+            assert_sorts_like_std(&rng.vec(len, u32::MAX as u64));
+        }
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+        for len in SIZES {
+            assert_sorts_like_std(&rng.vec(len, 4));
+        }
+    }
+
+    #[test]
+    fn test_sorted_and_reversed() {
+        let mut rng = XorShift(0xDEAD_BEEF_CAFE_F00D);
+        for len in SIZES {
+            let mut data = rng.vec(len, 1000);
+            data.sort();
+            assert_sorts_like_std(&data);
+            data.reverse();
+            assert_sorts_like_std(&data);
+        }
+    }
+
+    #[test]
+    fn test_sawtooth() {
+        for len in SIZES {
+            for period in [2, 5, 64] {
+                let ascending: Vec<i32> = (0..len).map(|i| (i % period) as i32).collect();
+                assert_sorts_like_std(&ascending);
+                let descending: Vec<i32> = ascending.iter().map(|n| -n).collect();
+                assert_sorts_like_std(&descending);
+            }
+        }
+    }
 }
@@ -1,11 +1,18 @@
-use std::ops::{Index, Range, RangeFrom, RangeTo};
-use std::slice::{Chunks, ChunksMut, Iter};
+use std::collections::{BTreeSet, HashSet, TryReserveError};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use std::iter::Take;
+use std::ops::{Deref, DerefMut, Index, Range, RangeBounds, RangeFrom, RangeTo};
+use std::slice::{Chunks, ChunksExact, ChunksMut, RChunksExact, RSplit, Split};
+use std::str::Utf8Error;
+use std::string::FromUtf8Error;
+use std::{array, vec};
 
 /// A growable, generic list that resides on the stack if it's small,
 /// but is moved to the heap to grow larger if needed.
 /// This list is generic over the items it contains as well as the
 /// size of its buffer if it's on the stack.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LocalStorageVec<T, const N: usize> {
     // TODO add some variants containing data
     // to make the compiler happy
@@ -16,6 +23,19 @@ pub enum LocalStorageVec<T, const N: usize> {
     Heap(Vec<T>),
 }
 
+/// When a heap-backed [`LocalStorageVec`] should try to move back to the stack
+/// after its length drops to `N` or below. Applied by a [`ShrinkingVec`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShrinkPolicy {
+    /// Never move back to the stack.
+    #[default]
+    Never,
+    /// Only move back to the stack when the vec is cleared.
+    OnClear,
+    /// Move back to the stack after every `pop`, `remove`, `truncate` and `clear`.
+    Always,
+}
+
 // **Below `From` implementation is used in the tests and are therefore given. However,
 // you should have a thorough look at it as they contain various new concepts.**
 // This implementation is generic not only over the type `T`, but also over the
@@ -89,11 +109,11 @@ impl<T, const N: usize> AsMut<[T]> for LocalStorageVec<T, N> {
 }
 
 impl<T: Default + Clone, const N: usize> LocalStorageVec<T, N> {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self::from([])
     }
 
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         match self {
             LocalStorageVec::Stack { buf: _, len } => {
                 *len
@@ -104,9 +124,17 @@ impl<T: Default + Clone, const N: usize> LocalStorageVec<T, N> {
         }
     }
 
-    fn push(&mut self, elem: T) {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The `debug_assert!`s in `push`, `pop`, `insert` and `remove` check the internal
+    // invariants in debug builds (including tests), and are compiled out in release builds
+
+    pub fn push(&mut self, elem: T) {
         match self {
             LocalStorageVec::Stack { buf, len } => {
+                debug_assert!(*len <= N, "stack len (is {len}) should be <= N (is {N})");
                 if *len < buf.len() {
                     buf[*len] = elem;
                     *len += 1
@@ -122,9 +150,10 @@ impl<T: Default + Clone, const N: usize> LocalStorageVec<T, N> {
         }
     }
 
-    fn pop(&mut self) -> Option<T> {
+    pub fn pop(&mut self) -> Option<T> {
         match self {
             LocalStorageVec::Stack { buf, len } => {
+                debug_assert!(*len <= N, "stack len (is {len}) should be <= N (is {N})");
                 if *len == 0 {
                     None
                 } else {
@@ -138,9 +167,11 @@ impl<T: Default + Clone, const N: usize> LocalStorageVec<T, N> {
         }
     }
 
-    fn insert(&mut self, index: usize, elem: T) {
+    pub fn insert(&mut self, index: usize, elem: T) {
         match self {
             LocalStorageVec::Stack { buf, len } => {
+                debug_assert!(*len <= N, "stack len (is {len}) should be <= N (is {N})");
+                debug_assert!(index <= *len, "insertion index (is {index}) should be <= len (is {len})");
                 if *len != buf.len() {
                     for index in (index..*len).rev() {
                         buf[index + 1] = buf[index].clone();
@@ -161,14 +192,16 @@ impl<T: Default + Clone, const N: usize> LocalStorageVec<T, N> {
         }
     }
 
-    fn remove(&mut self, index: usize) -> T {
+    #[track_caller]
+    pub fn remove(&mut self, index: usize) -> T {
         match self {
             LocalStorageVec::Stack { buf, len } => {
-                if *len == 0 || index >= *len {
-                    panic!("Failed to get element of index {index} in array of len {len}")
+                debug_assert!(*len <= N, "stack len (is {len}) should be <= N (is {N})");
+                if index >= *len {
+                    panic!("removal index (is {index}) should be < len (is {len})")
                 } else {
                     let output = buf[index].clone();
-                    for index in index..*len {
+                    for index in index..*len - 1 {
                         buf[index] = buf[index + 1].clone();
                     }
                     *len -= 1;
@@ -181,9 +214,9 @@ impl<T: Default + Clone, const N: usize> LocalStorageVec<T, N> {
         }
     }
 
-    fn clear(&mut self) {
+    pub fn clear(&mut self) {
         match self {
-            LocalStorageVec::Stack { buf, len } => {
+            LocalStorageVec::Stack { buf: _, len } => {
                 *len = 0;
             }
             LocalStorageVec::Heap(vec) => {
@@ -192,324 +225,2735 @@ impl<T: Default + Clone, const N: usize> LocalStorageVec<T, N> {
         }
     }
 
-    fn iter(&self) -> LocalStorageVecIterator<T, N> {
+    pub fn truncate(&mut self, new_len: usize) {
+        match self {
+            LocalStorageVec::Stack { buf: _, len } => {
+                if new_len < *len {
+                    *len = new_len;
+                }
+            }
+            LocalStorageVec::Heap(vec) => {
+                vec.truncate(new_len);
+            }
+        }
+    }
+
+    /// Move a `Heap` vec back to the `Stack` variant if its elements fit
+    /// in the stack buffer. Otherwise, shrinks the capacity of the heap buffer.
+    pub fn shrink_to_fit(&mut self) {
+        if let LocalStorageVec::Heap(vec) = self {
+            if vec.len() <= N {
+                let len = vec.len();
+                let mut it = std::mem::take(vec).into_iter();
+                *self = Self::Stack {
+                    buf: [(); N].map(|_| it.next().unwrap_or_default()),
+                    len,
+                };
+            } else {
+                vec.shrink_to_fit();
+            }
+        }
+    }
+
+    pub fn iter(&self) -> LocalStorageVecIterator<'_, T, N> {
         LocalStorageVecIterator {
             data: self.as_ref(),
             index: 0,
         }
     }
 
-    fn chunks(&self, chunk_size: usize)->Chunks<'_, T>{
+    pub fn chunks(&self, chunk_size: usize)->Chunks<'_, T>{
         self.as_ref().chunks(chunk_size)
     }
 
-    fn deref(&self)->&[T]{
-        self.as_ref()
+    pub fn chunks_mut(&mut self, chunk_size: usize) ->ChunksMut<'_, T>{
+        self.as_mut().chunks_mut(chunk_size)
     }
 
-    fn chunks_mut(&mut self, chunk_size: usize) ->ChunksMut<'_, T>{
-        self.as_mut().chunks_mut(chunk_size)
+    /// Iterate over chunks of exactly `chunk_size` elements, starting at the end.
+    /// The leftover elements at the front are available through
+    /// [`RChunksExact::remainder`]. Panics if `chunk_size` is 0
+    pub fn rchunks_exact(&self, chunk_size: usize) -> RChunksExact<'_, T> {
+        self.as_ref().rchunks_exact(chunk_size)
     }
 
-    fn deref_mut(&mut self) ->&mut [T]{
-        self.as_mut()
+    /// Iterate over the sub-slices separated by elements matching `pred`
+    pub fn split<F: FnMut(&T) -> bool>(&self, pred: F) -> Split<'_, T, F> {
+        self.as_ref().split(pred)
     }
-}
 
-pub struct LocalStorageVecIterator<'a, T:'a, const N: usize> {
-    data: &'a[T],
-    index: usize,
-}
+    /// Like [`LocalStorageVec::split`], but starting at the end
+    pub fn rsplit<F: FnMut(&T) -> bool>(&self, pred: F) -> RSplit<'_, T, F> {
+        self.as_ref().rsplit(pred)
+    }
 
-impl<'a, T: Default + Clone, const N: usize> IntoIterator for &'a LocalStorageVec<T, N> {
-    type Item = T;
-    type IntoIter = LocalStorageVecIterator<'a, T, N>;
+    /// Combine the elements of `self` and `other` pairwise using `f`,
+    /// up to the length of the shorter of the two.
+    pub fn zip_with<U, V, F>(&self, other: &LocalStorageVec<U, N>, mut f: F) -> LocalStorageVec<V, N>
+    where
+        F: FnMut(&T, &U) -> V,
+        V: Default + Clone,
+    {
+        self.as_ref()
+            .iter()
+            .zip(other.as_ref())
+            .map(|(a, b)| f(a, b))
+            .collect()
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        Self::IntoIter {
-            data: self.as_ref(),
-            index: 0,
+    /// Remove all duplicate elements in a single pass, assuming the vec is sorted.
+    /// If the vec is not sorted, only consecutive duplicates are removed.
+    pub fn dedup_sorted(&mut self)
+    where
+        T: PartialEq,
+    {
+        let slice = self.as_mut();
+        if slice.is_empty() {
+            return;
+        }
+
+        let mut write = 1;
+        for read in 1..slice.len() {
+            if slice[read] != slice[write - 1] {
+                slice.swap(read, write);
+                write += 1;
+            }
         }
+
+        self.truncate(write);
     }
-}
 
-impl<T: Default + Clone, const N: usize> Iterator for LocalStorageVecIterator<'_, T, N> {
-    type Item = T;
+    /// Collect the items of `iter` into a `Heap` variant, regardless of how many
+    /// items there are. Useful when the collection is known to be large.
+    pub fn collect_on_heap<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Heap(iter.into_iter().collect())
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.data.len() {
-            None
-        } else {
-            let output = self.data[self.index].clone();
-            self.index += 1;
-            Some(output)
-        }
+    /// Clone into a `Heap`-backed vec, regardless of the variant of `self`
+    pub fn clone_into_heap(&self) -> LocalStorageVec<T, N> {
+        Self::Heap(self.as_ref().to_vec())
     }
-}
 
-impl<T: Default + Clone, const N: usize> Index<usize> for LocalStorageVec<T, N> {
-    type Output = T;
+    /// Replace `self` with an empty `Stack` vec, returning the former contents
+    pub fn take(&mut self) -> LocalStorageVec<T, N> {
+        std::mem::take(self)
+    }
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.as_ref()[index]
+    /// Move all elements out into a `Vec`, leaving `self` as an empty `Stack` vec
+    pub fn drain_to_vec(&mut self) -> Vec<T> {
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                let drained = buf[..*len].iter_mut().map(std::mem::take).collect();
+                *len = 0;
+                drained
+            }
+            LocalStorageVec::Heap(vec) => {
+                let drained = std::mem::take(vec);
+                self.shrink_to_fit();
+                drained
+            }
+        }
     }
-}
 
-impl<T: Default + Clone, const N: usize> Index<RangeTo<usize>> for LocalStorageVec<T, N> {
-    type Output = [T];
+    /// Apply `f` to the element at `index`. Returns `false` if `index` is out of range,
+    /// in which case `f` is not called.
+    pub fn update<F: FnOnce(&mut T)>(&mut self, index: usize, f: F) -> bool {
+        match self.as_mut().get_mut(index) {
+            Some(elem) => {
+                f(elem);
+                true
+            }
+            None => false,
+        }
+    }
 
-    fn index(&self, index: RangeTo<usize>) -> &Self::Output {
-        &self.as_ref()[index]
+    /// Overwrite the elements of `self` with the items of `iter`, stopping at
+    /// whichever ends first. The length of `self` never changes.
+    pub fn fill_from_iter<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for (slot, elem) in self.as_mut().iter_mut().zip(iter) {
+            *slot = elem;
+        }
     }
-}
 
-impl<T: Default + Clone, const N: usize> Index<RangeFrom<usize>> for LocalStorageVec<T, N> {
-    type Output = [T];
+    /// The number of bytes allocated on the heap to store the elements.
+    /// This is `0` as long as the vec resides on the stack.
+    pub fn heap_bytes(&self) -> usize {
+        match self {
+            LocalStorageVec::Stack { .. } => 0,
+            LocalStorageVec::Heap(vec) => vec.capacity() * std::mem::size_of::<T>(),
+        }
+    }
 
-    fn index(&self, index: RangeFrom<usize>) -> &Self::Output {
-        &self.as_ref()[index]
+    /// Whether the vec has spilled over to the heap
+    pub fn is_spilled(&self) -> bool {
+        matches!(self, LocalStorageVec::Heap(_))
     }
-}
 
-impl<T: Default + Clone, const N: usize> Index<Range<usize>> for LocalStorageVec<T, N> {
-    type Output = [T];
+    /// Whether the elements are stored in the stack buffer
+    pub fn is_stack(&self) -> bool {
+        matches!(self, LocalStorageVec::Stack { .. })
+    }
 
-    fn index(&self, index: Range<usize>) -> &Self::Output {
-        &self.as_ref()[index]
+    /// Whether the elements are stored on the heap, same as [`LocalStorageVec::is_spilled`]
+    pub fn is_heap(&self) -> bool {
+        self.is_spilled()
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::LocalStorageVec;
+    /// A short summary of where the elements are stored, along with the length and
+    /// capacity, e.g. `"Stack(len=3, cap=8)"` or `"Heap(len=300, cap=512)"`
+    pub fn describe(&self) -> String {
+        match self {
+            LocalStorageVec::Stack { len, .. } => format!("Stack(len={len}, cap={N})"),
+            LocalStorageVec::Heap(vec) => format!("Heap(len={}, cap={})", vec.len(), vec.capacity()),
+        }
+    }
 
-    #[test]
-    // Don't remove the #[ignore] attribute or your tests will take forever!
-    #[ignore = "This test is just to validate the definition of `LocalStorageVec`. If it compiles, all is OK"]
-    #[allow(unreachable_code, unused_variables)]
-    fn it_compiles() {
-        // Here's a trick to 'initialize' a type while not actually
-        // creating a value: an infinite `loop` expression diverges
-        // and evaluates to the 'never type' `!`, which, as is can never
-        // actually be instantiated, coerces to any other type.
-        // Some other ways of diverging are by calling the `panic!` or the `todo!`
-        // macros.
-        // More info:
-        // - https://doc.rust-lang.org/rust-by-example/fn/diverging.html
-        // - https://doc.rust-lang.org/reference/expressions/loop-expr.html#infinite-loops
-        let vec: LocalStorageVec<u32, 10> = loop {};
-        match vec {
-            LocalStorageVec::Stack { buf, len } => {
-                let _buf: [u32; 10] = buf;
-                let _len: usize = len;
-            }
-            LocalStorageVec::Heap(v) => {
-                let _v: Vec<u32> = v;
+    /// Push an element, dropping the oldest element instead of moving to the heap
+    /// if the stack buffer is full. On the `Heap` variant, this behaves like `push`.
+    pub fn push_ring(&mut self, elem: T) {
+        match self {
+            LocalStorageVec::Stack { buf, len } if *len == N => {
+                if N > 0 {
+                    buf.rotate_left(1);
+                    buf[N - 1] = elem;
+                }
             }
+            _ => self.push(elem),
         }
     }
 
-    // Uncomment me for part B
-    #[test]
-    fn it_from_vecs() {
-        // The `vec!` macro creates a `Vec<T>` in a way that resembles
-        // array-initialization syntax.
-        let vec: LocalStorageVec<usize, 10> = LocalStorageVec::from(vec![1, 2, 3]);
-        // Assert that the call to `from` indeed yields a `Heap` variant
-        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+    /// Split off the first `C` elements as an array reference, along with the rest.
+    /// Returns `None` if there are fewer than `C` elements.
+    pub fn split_first_chunk<const C: usize>(&self) -> Option<(&[T; C], &[T])> {
+        self.as_ref().split_first_chunk()
+    }
 
-        let vec: LocalStorageVec<usize, 2> = LocalStorageVec::from(vec![1, 2, 3]);
+    /// The first `C` elements as an array reference, or `None` if there are fewer than `C` elements
+    pub fn first_chunk<const C: usize>(&self) -> Option<&[T; C]> {
+        self.as_ref().first_chunk()
+    }
 
-        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+    /// The last `C` elements as an array reference, or `None` if there are fewer than `C` elements
+    pub fn last_chunk<const C: usize>(&self) -> Option<&[T; C]> {
+        self.as_ref().last_chunk()
     }
 
-    // Uncomment me for part C
-    #[test]
-    fn it_as_refs() {
-        let vec: LocalStorageVec<i32, 256> = LocalStorageVec::from([0; 128]);
-        let slice: &[i32] = vec.as_ref();
-        assert!(slice.len() == 128);
-        let vec: LocalStorageVec<i32, 32> = LocalStorageVec::from([0; 128]);
-        let slice: &[i32] = vec.as_ref();
-        assert!(slice.len() == 128);
+    /// Borrow the stack buffer as an array, if the vec is on the stack and completely full
+    pub fn as_array_ref(&self) -> Option<&[T; N]> {
+        match self {
+            LocalStorageVec::Stack { buf, len } if *len == N => Some(buf),
+            _ => None,
+        }
+    }
 
-        let mut vec: LocalStorageVec<i32, 256> = LocalStorageVec::from([0; 128]);
-        let slice_mut: &[i32] = vec.as_mut();
-        assert!(slice_mut.len() == 128);
-        let mut vec: LocalStorageVec<i32, 32> = LocalStorageVec::from([0; 128]);
-        let slice_mut: &[i32] = vec.as_mut();
-        assert!(slice_mut.len() == 128);
+    /// Remove the elements at the passed indices in a single pass, returning them in order.
+    /// Panics if the indices are not sorted and unique, or if any of them is out of range.
+    #[track_caller]
+    pub fn remove_all(&mut self, indices: &[usize]) -> Vec<T> {
+        let len = self.len();
+        assert!(
+            indices.windows(2).all(|pair| pair[0] < pair[1]),
+            "removal indices should be sorted and unique"
+        );
+        if let Some(&index) = indices.last() {
+            assert!(index < len, "removal index (is {index}) should be < len (is {len})");
+        }
+
+        let slice = self.as_mut();
+        let mut removed = Vec::with_capacity(indices.len());
+        let mut indices = indices.iter().peekable();
+        let mut write = 0;
+
+        for read in 0..len {
+            if indices.next_if_eq(&&read).is_some() {
+                removed.push(std::mem::take(&mut slice[read]));
+            } else {
+                slice.swap(read, write);
+                write += 1;
+            }
+        }
+
+        self.truncate(write);
+        removed
     }
 
-    // Uncomment me for part D
-    #[test]
-    fn it_constructs() {
-        let vec: LocalStorageVec<usize, 10> = LocalStorageVec::new();
-        // Assert that the call to `new` indeed yields a `Stack` variant with zero length
-        assert!(matches!(vec, LocalStorageVec::Stack { buf: _, len: 0 }));
+    /// Apply `f` to each chunk of `chunk_size` elements, starting from a clone of `init`
+    /// for every chunk, and collect the per-chunk results.
+    /// The last chunk is shorter if the length is not a multiple of `chunk_size`
+    pub fn chunks_fold<B, F>(&self, chunk_size: usize, init: B, mut f: F) -> Vec<B>
+    where
+        B: Clone,
+        F: FnMut(B, &[T]) -> B,
+    {
+        self.chunks(chunk_size).map(|chunk| f(init.clone(), chunk)).collect()
     }
 
-    // Uncomment me for part D
-    #[test]
-    fn it_lens() {
-        let vec: LocalStorageVec<_, 3> = LocalStorageVec::from([0, 1, 2]);
-        assert_eq!(vec.len(), 3);
-        let vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 2]);
-        assert_eq!(vec.len(), 3);
+    /// Iterate over every `step`-th element, starting at the first one.
+    /// Panics if `step` is `0`.
+    pub fn stride(&self, step: usize) -> impl Iterator<Item = &T> {
+        self.as_ref().iter().step_by(step)
     }
 
-    // Uncomment me for part D
-    #[test]
-    fn it_pushes() {
-        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::new();
-        for value in 0..128 {
-            vec.push(value);
+    /// Call `f` on each overlapping window of `size` elements, one window at a time,
+    /// from front to back. Changes made to a window are visible in the next ones.
+    /// Panics if `size` is `0`.
+    pub fn for_each_window_mut<F: FnMut(&mut [T])>(&mut self, size: usize, mut f: F) {
+        assert!(size != 0, "window size must be non-zero");
+
+        let slice = self.as_mut();
+        if size > slice.len() {
+            return;
         }
-        assert!(matches!(vec, LocalStorageVec::Stack { len: 128, .. }));
-        for value in 128..256 {
-            vec.push(value);
+
+        for start in 0..=slice.len() - size {
+            f(&mut slice[start..start + size]);
         }
-        assert!(matches!(vec, LocalStorageVec::Heap(v) if v.len() == 256))
     }
 
-    // Uncomment me for part D
-    #[test]
-    fn it_pops() {
-        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 128]);
-        for _ in 0..128 {
-            assert_eq!(vec.pop(), Some(0))
-        }
-        assert_eq!(vec.pop(), None);
+    /// Insert `elem` at the position that keeps the vec sorted, assuming it already is.
+    /// Returns the index at which `elem` was inserted.
+    pub fn insert_sorted(&mut self, elem: T) -> usize
+    where
+        T: Ord,
+    {
+        let index = match self.as_ref().binary_search(&elem) {
+            Ok(index) | Err(index) => index,
+        };
+        self.insert(index, elem);
+        index
+    }
 
-        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 256]);
-        for _ in 0..256 {
-            assert_eq!(vec.pop(), Some(0))
-        }
-        assert_eq!(vec.pop(), None);
+    /// Find the index of the first element for which `pred` returns `false`,
+    /// assuming the vec is partitioned according to `pred`
+    pub fn partition_point<P: FnMut(&T) -> bool>(&self, pred: P) -> usize {
+        self.as_ref().partition_point(pred)
+    }
 
-        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::from(vec![0; 256]);
-        for _ in 0..256 {
-            assert_eq!(vec.pop(), Some(0))
+    /// Remove consecutive duplicate elements, keeping the last element of each run
+    pub fn dedup_keep_last(&mut self)
+    where
+        T: PartialEq,
+    {
+        let slice = self.as_mut();
+        let len = slice.len();
+
+        let mut write = 0;
+        for read in 0..len {
+            if read + 1 < len && slice[read] == slice[read + 1] {
+                continue;
+            }
+            slice.swap(read, write);
+            write += 1;
         }
-        assert_eq!(vec.pop(), None);
+
+        self.truncate(write);
     }
 
-    // Uncomment me for part D
-    #[test]
-    fn it_inserts() {
-        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
-        vec.insert(1, 3);
-        assert!(matches!(
-            vec,
-            LocalStorageVec::Stack {
-                buf: [0, 3, 1, 2],
-                len: 4
-            }
-        ));
+    /// Keep only the elements whose index is in `keep`, moving them to the front in order
+    pub fn retain_indices(&mut self, keep: &BTreeSet<usize>) {
+        let slice = self.as_mut();
 
-        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2, 3]);
-        vec.insert(1, 3);
-        assert!(matches!(vec, LocalStorageVec::Heap { .. }));
-        assert_eq!(vec.as_ref(), &[0, 3, 1, 2, 3]);
+        let mut write = 0;
+        for &read in keep.range(..slice.len()) {
+            slice.swap(read, write);
+            write += 1;
+        }
 
-        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2, 3, 4]);
-        vec.insert(1, 3);
-        assert!(matches!(vec, LocalStorageVec::Heap { .. }));
-        assert_eq!(vec.as_ref(), &[0, 3, 1, 2, 3, 4])
+        self.truncate(write);
     }
 
-    // Uncomment me for part D
-    #[test]
-    fn it_removes() {
-        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
-        let elem = vec.remove(1);
-        dbg!(&vec);
-        assert!(matches!(
-            vec,
-            LocalStorageVec::Stack {
-                buf: [0, 2, _, _],
-                len: 2
+    /// Remove all elements that are equal to `T::default()`, moving the others to the front
+    pub fn compact(&mut self)
+    where
+        T: PartialEq,
+    {
+        let empty = T::default();
+        let slice = self.as_mut();
+
+        let mut write = 0;
+        for read in 0..slice.len() {
+            if slice[read] != empty {
+                slice.swap(read, write);
+                write += 1;
             }
-        ));
-        assert_eq!(elem, 1);
+        }
 
-        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 2]);
-        let elem = vec.remove(1);
-        assert!(matches!(vec, LocalStorageVec::Heap(..)));
-        assert_eq!(vec.as_ref(), &[0, 2]);
-        assert_eq!(elem, 1);
+        self.truncate(write);
     }
 
-    // Uncomment me for part D
-    #[test]
-    fn it_clears() {
-        let mut vec: LocalStorageVec<_, 10> = LocalStorageVec::from([0, 1, 2, 3]);
-        assert!(matches!(vec, LocalStorageVec::Stack { buf: _, len: 4 }));
-        vec.clear();
-        assert_eq!(vec.len(), 0);
+    /// Serialize each element as JSON on its own line
+    #[cfg(feature = "serde")]
+    pub fn to_ndjson(&self) -> Result<String, serde_json::Error>
+    where
+        T: serde::Serialize,
+    {
+        let mut output = String::new();
+        for elem in self.as_ref() {
+            output.push_str(&serde_json::to_string(elem)?);
+            output.push('\n');
+        }
+        Ok(output)
+    }
 
-        let mut vec: LocalStorageVec<_, 3> = LocalStorageVec::from([0, 1, 2, 3]);
-        assert!(matches!(vec, LocalStorageVec::Heap(_)));
-        vec.clear();
+    /// Deserialize a vec from JSON lines, one element per line. Empty lines are skipped.
+    #[cfg(feature = "serde")]
+    pub fn from_ndjson(ndjson: &str) -> Result<Self, serde_json::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        ndjson
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect()
+    }
+
+    /// Iterate over chunks of exactly `chunk_size` elements, also returning the
+    /// remaining elements that do not fit in a chunk
+    pub fn chunks_exact_with_remainder(&self, chunk_size: usize) -> (ChunksExact<'_, T>, &[T]) {
+        let chunks = self.as_ref().chunks_exact(chunk_size);
+        let remainder = chunks.remainder();
+        (chunks, remainder)
+    }
+
+    /// Apply `f` to every element in place
+    pub fn map_in_place<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        for elem in self.as_mut() {
+            f(elem);
+        }
+    }
+
+    /// Remove the leading elements for which `pred` returns `true`,
+    /// moving the remaining elements to the front
+    pub fn drop_while_front<P: FnMut(&T) -> bool>(&mut self, mut pred: P) {
+        let slice = self.as_mut();
+        let len = slice.len();
+        let dropped = slice.iter().position(|elem| !pred(elem)).unwrap_or(len);
+
+        slice.rotate_left(dropped);
+        self.truncate(len - dropped);
+    }
+
+    /// Iterate over all elements once, starting at `start` and wrapping around to the front.
+    /// Panics if `start` is out of range on a non-empty vec.
+    #[track_caller]
+    pub fn iter_circular(&self, start: usize) -> impl Iterator<Item = &T> {
+        let slice = self.as_ref();
+        let start = if slice.is_empty() {
+            0
+        } else {
+            assert!(start < slice.len(), "start (is {start}) should be < len (is {})", slice.len());
+            start
+        };
+
+        let (head, tail) = slice.split_at(start);
+        tail.iter().chain(head)
+    }
+
+    /// Try to reserve room for at least `additional` more elements. If the stack buffer
+    /// is too small, the elements are moved to a newly allocated heap buffer.
+    /// Returns `Err` if the capacity overflows or the allocation fails, leaving `self` unchanged.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                let required = len.saturating_add(additional);
+                if required <= N {
+                    return Ok(());
+                }
+
+                let mut vec = Vec::new();
+                vec.try_reserve(required)?;
+                vec.extend(buf[..*len].iter_mut().map(std::mem::take));
+                *self = Self::Heap(vec);
+                Ok(())
+            }
+            LocalStorageVec::Heap(vec) => {
+                vec.try_reserve(additional)
+            }
+        }
+    }
+
+    /// Insert `elem` at `index`, shifting all elements after it to the right.
+    /// If the stack buffer is full, the elements are moved to the heap before shifting.
+    /// Panics if `index > len`.
+    #[track_caller]
+    pub fn shift_insert(&mut self, index: usize, elem: T) {
+        let len = self.len();
+        assert!(index <= len, "insertion index (is {index}) should be <= len (is {len})");
+
+        if let LocalStorageVec::Stack { buf, len } = self {
+            if *len == N {
+                let vec = buf.iter_mut().map(std::mem::take).collect();
+                *self = Self::Heap(vec);
+            }
+        }
+
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                buf[*len] = elem;
+                buf[index..=*len].rotate_right(1);
+                *len += 1;
+            }
+            LocalStorageVec::Heap(vec) => {
+                vec.insert(index, elem);
+            }
+        }
+    }
+
+    /// Get a reference to the element that the next `pop` would return, without removing it
+    pub fn peek(&self) -> Option<&T> {
+        self.as_ref().last()
+    }
+
+    /// Index of the element with the maximum key, or `None` if empty. If several
+    /// elements are maximal, the index of the last one is returned, like `Iterator::max_by_key`
+    pub fn position_max_by_key<K: Ord, F: FnMut(&T) -> K>(&self, mut f: F) -> Option<usize> {
+        self.as_ref()
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, elem)| f(elem))
+            .map(|(index, _)| index)
+    }
+
+    /// Remove and return the first element in O(1) by replacing it with the
+    /// last element, so the order is not preserved. Returns `None` if empty
+    pub fn swap_remove_front(&mut self) -> Option<T> {
+        let last = self.len().checked_sub(1)?;
+        self.as_mut().swap(0, last);
+        self.pop()
+    }
+
+    /// Binary search a vec sorted by the key extracted with `f`, like
+    /// [`slice::binary_search_by_key`]
+    pub fn binary_search_by_key<B: Ord, F: FnMut(&T) -> B>(&self, b: &B, f: F) -> Result<usize, usize> {
+        self.as_ref().binary_search_by_key(b, f)
+    }
+
+    /// Push `elem` only if the vec holds fewer than `max_len` elements,
+    /// giving it back as `Err` otherwise. This limit is independent of `N`
+    pub fn checked_push(&mut self, elem: T, max_len: usize) -> Result<(), T> {
+        if self.len() >= max_len {
+            return Err(elem);
+        }
+
+        self.push(elem);
+        Ok(())
+    }
+
+    /// Rotate the element at `index` and the ones after it to the front, as
+    /// in `rotate_left(index)`, e.g. to move recently used elements up front.
+    /// Panics if `index >= len`
+    #[track_caller]
+    pub fn rotate_to_front(&mut self, index: usize) {
+        let len = self.len();
+        assert!(index < len, "rotation index (is {index}) should be < len (is {len})");
+        self.as_mut().rotate_left(index);
+    }
+
+    /// Split the vec into runs of consecutive elements for which `same_group`
+    /// holds between each neighbouring pair, like [`slice::chunk_by`]
+    pub fn group_by<F: FnMut(&T, &T) -> bool>(&self, same_group: F) -> Vec<&[T]> {
+        self.as_ref().chunk_by(same_group).collect()
+    }
+
+    /// Swap the contents of the equal-length ranges `a` and `b`.
+    /// Panics if the ranges differ in length, overlap, or are out of bounds
+    #[track_caller]
+    pub fn swap_ranges(&mut self, a: Range<usize>, b: Range<usize>) {
+        let len = self.len();
+        assert_eq!(a.len(), b.len(), "ranges {a:?} and {b:?} should be of equal length");
+        assert!(a.end <= len && b.end <= len, "ranges {a:?} and {b:?} should be within len (is {len})");
+
+        let (first, second) = if a.start <= b.start { (a, b) } else { (b, a) };
+        assert!(first.end <= second.start || first.is_empty(), "ranges {first:?} and {second:?} should not overlap");
+
+        let (left, right) = self.as_mut().split_at_mut(second.start);
+        left[first].swap_with_slice(&mut right[..second.len()]);
+    }
+
+    /// Keep only the elements for which `f` returns `true`, like `Vec::retain_mut`,
+    /// returning the removed elements in their original order
+    pub fn retain_collecting<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) -> Vec<T> {
+        let slice = self.as_mut();
+        let mut removed = Vec::new();
+
+        let mut write = 0;
+        for read in 0..slice.len() {
+            if f(&mut slice[read]) {
+                slice.swap(read, write);
+                write += 1;
+            } else {
+                removed.push(std::mem::take(&mut slice[read]));
+            }
+        }
+
+        self.truncate(write);
+        removed
+    }
+
+    /// A new vec with `sep` between each pair of neighbouring elements,
+    /// which stays on the stack if it fits
+    pub fn join_with(&self, sep: T) -> LocalStorageVec<T, N> {
+        let mut joined = LocalStorageVec::new();
+        for (index, elem) in self.as_ref().iter().enumerate() {
+            if index > 0 {
+                joined.push(sep.clone());
+            }
+            joined.push(elem.clone());
+        }
+        joined
+    }
+
+    /// Keep only the first `n` elements for which `f` returns `true`,
+    /// removing all other elements
+    pub fn retain_first_n<F: FnMut(&T) -> bool>(&mut self, n: usize, mut f: F) {
+        let slice = self.as_mut();
+
+        let mut write = 0;
+        for read in 0..slice.len() {
+            if write == n {
+                break;
+            }
+            if f(&slice[read]) {
+                slice.swap(read, write);
+                write += 1;
+            }
+        }
+
+        self.truncate(write);
+    }
+
+    /// Clone each chunk of `chunk_size` elements into its own vec, which stays on
+    /// the stack if it fits. The last chunk may be shorter. Panics if `chunk_size` is 0
+    pub fn chunked(&self, chunk_size: usize) -> Vec<LocalStorageVec<T, N>> {
+        self.chunks(chunk_size)
+            .map(|chunk| chunk.iter().cloned().collect())
+            .collect()
+    }
+
+    /// Remove the first `at` elements and return them in a new vec, moving the
+    /// remaining elements to the front. Panics if `at > len`
+    #[track_caller]
+    pub fn split_off_front(&mut self, at: usize) -> LocalStorageVec<T, N> {
+        let len = self.len();
+        assert!(at <= len, "split index (is {at}) should be <= len (is {len})");
+
+        let front = self.as_mut()[..at].iter_mut().map(std::mem::take).collect();
+        self.as_mut().rotate_left(at);
+        self.truncate(len - at);
+        front
+    }
+
+    /// Iterate over the elements in arrays of `C` elements, leaving out the
+    /// remaining elements that don't fill a whole array. Panics if `C` is 0
+    pub fn array_chunks<const C: usize>(&self) -> impl Iterator<Item = &[T; C]> {
+        self.as_ref().as_chunks::<C>().0.iter()
+    }
+
+    /// Count the elements for which `pred` returns `true` and `false` in a single pass,
+    /// returned as `(matching, non_matching)`
+    pub fn partition_counts<F: Fn(&T) -> bool>(&self, pred: F) -> (usize, usize) {
+        let matching = self.as_ref().iter().filter(|elem| pred(elem)).count();
+        (matching, self.len() - matching)
+    }
+
+    /// Remove all duplicate elements, keeping the first occurrence of each.
+    /// Unlike [`LocalStorageVec::dedup_sorted`], duplicates need not be consecutive
+    pub fn retain_unique(&mut self)
+    where
+        T: Eq + Hash,
+    {
+        let slice = self.as_mut();
+        let mut seen = HashSet::with_capacity(slice.len());
+
+        let mut write = 0;
+        for read in 0..slice.len() {
+            if seen.insert(slice[read].clone()) {
+                slice.swap(read, write);
+                write += 1;
+            }
+        }
+
+        self.truncate(write);
+    }
+
+    /// Remove and return the first `n` elements (or all elements if there are
+    /// fewer than `n`), moving the remaining elements to the front
+    pub fn pop_front_n(&mut self, n: usize) -> Vec<T> {
+        let n = n.min(self.len());
+        self.split_off_front(n).into_vec()
+    }
+
+    /// Iterate over each pair of neighbouring elements, e.g. to compute the
+    /// differences between consecutive elements
+    pub fn pairs(&self) -> impl Iterator<Item = (&T, &T)> {
+        self.as_ref().windows(2).map(|pair| (&pair[0], &pair[1]))
+    }
+
+    /// Resize the vec to `new_len`, like `Vec::resize_with`: growing by pushing values
+    /// returned by `f` (moving to the heap if they don't fit), or shrinking by truncating
+    pub fn resize_with<F: FnMut() -> T>(&mut self, new_len: usize, mut f: F) {
+        let len = self.len();
+        if new_len <= len {
+            self.truncate(new_len);
+            return;
+        }
+
+        if let LocalStorageVec::Heap(vec) = self {
+            vec.resize_with(new_len, f);
+        } else {
+            for _ in len..new_len {
+                self.push(f());
+            }
+        }
+    }
+
+    /// Iterate over windows of `size` elements starting every `step` elements,
+    /// leaving out a last window that would not be complete.
+    /// Panics if `size` or `step` is 0
+    pub fn overlapping_chunks(&self, size: usize, step: usize) -> impl Iterator<Item = &[T]> {
+        self.as_ref().windows(size).step_by(step)
+    }
+
+    /// Apply `f` to each element in `range`, leaving the other elements untouched.
+    /// Panics if `range` is out of bounds
+    #[track_caller]
+    pub fn modify_range<R: RangeBounds<usize>, F: FnMut(&mut T)>(&mut self, range: R, f: F) {
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        self.as_mut()[bounds].iter_mut().for_each(f);
+    }
+
+    /// Call the fallible `f` on each element by reference, stopping at the first error
+    pub fn try_for_each<E, F: FnMut(&T) -> Result<(), E>>(&self, f: F) -> Result<(), E> {
+        self.as_ref().iter().try_for_each(f)
+    }
+
+    /// Reorder the vec such that the element at `n` is the `n`th smallest, with all smaller
+    /// elements before it and all larger elements after it, like [`slice::select_nth_unstable`].
+    /// Panics if `n >= len`
+    #[track_caller]
+    pub fn select_nth_unstable(&mut self, n: usize) -> (&mut [T], &mut T, &mut [T])
+    where
+        T: Ord,
+    {
+        self.as_mut().select_nth_unstable(n)
+    }
+
+    /// Index of the last element for which `pred` returns `true`, or `None` if there is none
+    pub fn rposition<P: FnMut(&T) -> bool>(&self, pred: P) -> Option<usize> {
+        self.as_ref().iter().rposition(pred)
+    }
+
+    /// Remove consecutive elements for which `same_bucket` returns `true`, keeping the
+    /// first of each run. Like [`Vec::dedup_by`], `same_bucket` receives the candidate
+    /// element first and the previously kept element second.
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same_bucket: F) {
+        let slice = self.as_mut();
+        if slice.is_empty() {
+            return;
+        }
+
+        let mut write = 1;
+        for read in 1..slice.len() {
+            let (kept, rest) = slice.split_at_mut(read);
+            if !same_bucket(&mut rest[0], &mut kept[write - 1]) {
+                slice.swap(read, write);
+                write += 1;
+            }
+        }
+
+        self.truncate(write);
+    }
+
+    /// Keep only the elements for which `f` returns `true`, passing each element's
+    /// original index along with it
+    pub fn retain_indexed<F: FnMut(usize, &T) -> bool>(&mut self, mut f: F) {
+        let slice = self.as_mut();
+
+        let mut write = 0;
+        for read in 0..slice.len() {
+            if f(read, &slice[read]) {
+                slice.swap(read, write);
+                write += 1;
+            }
+        }
+
+        self.truncate(write);
+    }
+
+    /// Drop all elements and the heap allocation, if any, leaving an empty `Stack` vec.
+    /// Unlike `clear`, this also drops the elements left in the stack buffer.
+    pub fn clear_and_shrink(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Append elements from `iter` until it is exhausted or the vec holds `max_total_len`
+    /// elements, returning the number of elements added. No more items than needed are taken
+    /// from `iter`
+    pub fn extend_bounded<I: IntoIterator<Item = T>>(&mut self, iter: I, max_total_len: usize) -> usize {
+        let room = max_total_len.saturating_sub(self.len());
+        let mut added = 0;
+        for elem in iter.into_iter().take(room) {
+            self.push(elem);
+            added += 1;
+        }
+        added
+    }
+
+    /// Slide the vec forward like a window: drop the first `drop_front` elements and append
+    /// `push_back`, moving to the heap if the result doesn't fit. Panics if `drop_front > len`
+    #[track_caller]
+    pub fn advance_window(&mut self, drop_front: usize, push_back: &[T]) {
+        let len = self.len();
+        assert!(drop_front <= len, "window advance (is {drop_front}) should be <= len (is {len})");
+
+        self.as_mut().rotate_left(drop_front);
+        self.truncate(len - drop_front);
+        for elem in push_back {
+            self.push(elem.clone());
+        }
+    }
+
+    /// Append `additional` clones of `value`, moving to the heap at most once if they
+    /// don't fit in the stack buffer. Panics if the capacity overflows
+    #[track_caller]
+    pub fn reserve_and_fill(&mut self, additional: usize, value: T) {
+        self.try_reserve(additional).expect("capacity overflow");
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                buf[*len..*len + additional].fill(value);
+                *len += additional;
+            }
+            LocalStorageVec::Heap(vec) => {
+                vec.resize(vec.len() + additional, value);
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> LocalStorageVec<T, N> {
+    /// Convert into a `Vec`, moving the elements
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                buf.into_iter().take(len).collect()
+            }
+            LocalStorageVec::Heap(vec) => {
+                vec
+            }
+        }
+    }
+
+    /// Convert into a boxed slice without spare capacity, moving the elements
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        self.into_vec().into_boxed_slice()
+    }
+
+    /// Apply the fallible `f` to each element in order, returning the first
+    /// error encountered, or the vec of mapped elements if there was none
+    pub fn try_map<U, E, F>(self, f: F) -> Result<LocalStorageVec<U, N>, E>
+    where
+        U: Default + Clone,
+        F: FnMut(T) -> Result<U, E>,
+    {
+        self.into_vec().into_iter().map(f).collect()
+    }
+
+    /// Apply `f` to each element in order, keeping only the `Some` results in a
+    /// new vec, which stays on the stack if it fits
+    pub fn filter_map_into<U, F>(self, f: F) -> LocalStorageVec<U, N>
+    where
+        U: Default + Clone,
+        F: FnMut(T) -> Option<U>,
+    {
+        self.into_iter().filter_map(f).collect()
+    }
+
+    /// View the elements as their raw bytes, in native byte order
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8]
+    where
+        T: bytemuck::Pod,
+    {
+        bytemuck::cast_slice(self.as_ref())
+    }
+
+    /// View the elements as their raw bytes mutably, in native byte order
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8]
+    where
+        T: bytemuck::Pod,
+    {
+        bytemuck::cast_slice_mut(self.as_mut())
+    }
+
+    /// Copy the live elements into a new `Vec` with a single `extend_from_slice`.
+    pub fn to_owned_vec(&self) -> Vec<T>
+    where
+        T: Copy,
+    {
+        let mut vec = Vec::with_capacity(self.len());
+        vec.extend_from_slice(self.as_ref());
+        vec
+    }
+
+    /// Fold the elements into an accumulator by reference, without cloning them
+    pub fn fold<B, F: FnMut(B, &T) -> B>(&self, init: B, f: F) -> B {
+        self.as_ref().iter().fold(init, f)
+    }
+
+    /// Like [`LocalStorageVec::fold`], but returns the accumulator after each element
+    /// instead of only the final one, e.g. the prefix sums of the elements
+    pub fn running_fold<B: Clone, F: FnMut(&B, &T) -> B>(&self, init: B, mut f: F) -> Vec<B> {
+        let mut acc = init;
+        let mut running = Vec::with_capacity(self.len());
+        for elem in self.as_ref() {
+            acc = f(&acc, elem);
+            running.push(acc.clone());
+        }
+        running
+    }
+}
+
+impl<T: Default + Clone, const N: usize, const M: usize> LocalStorageVec<LocalStorageVec<T, N>, M> {
+    /// Concatenate all inner vecs in order into a single vec, moving the elements
+    pub fn flatten(self) -> LocalStorageVec<T, N> {
+        self.into_vec()
+            .into_iter()
+            .flat_map(LocalStorageVec::into_vec)
+            .collect()
+    }
+}
+
+impl<const N: usize> LocalStorageVec<u8, N> {
+    /// Build a `String` from the bytes, failing if they are not valid UTF-8
+    pub fn try_to_string(&self) -> Result<String, Utf8Error> {
+        std::str::from_utf8(self.as_ref()).map(str::to_owned)
+    }
+
+    /// Move the bytes into a `String`, failing if they are not valid UTF-8
+    pub fn try_into_string(self) -> Result<String, FromUtf8Error> {
+        String::from_utf8(self.into_vec())
+    }
+
+    /// Copy the bytes of `s`, which stay on the stack if they fit
+    pub fn from_str_bytes(s: &str) -> Self {
+        s.bytes().collect()
+    }
+}
+
+/// Display a `char` vec as the text it contains, which also provides `to_string`
+impl<const N: usize> Display for LocalStorageVec<char, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_ref().iter().try_for_each(|c| f.write_char(*c))
+    }
+}
+
+/// Appends the written bytes, moving to the heap if they don't fit, like `Vec<u8>` does
+impl<const N: usize> std::io::Write for LocalStorageVec<u8, N> {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.try_reserve(bytes.len())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::OutOfMemory, e))?;
+        match self {
+            LocalStorageVec::Stack { buf, len } => {
+                buf[*len..*len + bytes.len()].copy_from_slice(bytes);
+                *len += bytes.len();
+            }
+            LocalStorageVec::Heap(vec) => {
+                vec.extend_from_slice(bytes);
+            }
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: Default + Clone, const N: usize> Default for LocalStorageVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default + Clone, const N: usize> FromIterator<T> for LocalStorageVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        for elem in iter {
+            vec.push(elem);
+        }
+        vec
+    }
+}
+
+impl<T, const N: usize> Deref for LocalStorageVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+impl<T, const N: usize> DerefMut for LocalStorageVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut()
+    }
+}
+
+/// A [`LocalStorageVec`] that moves back to the stack as its [`ShrinkPolicy`] dictates.
+/// The vec can be read through `Deref`, but is only changed through the methods
+/// below, so that every operation shrinking it applies the policy.
+#[derive(Debug, Clone)]
+pub struct ShrinkingVec<T, const N: usize> {
+    vec: LocalStorageVec<T, N>,
+    shrink_policy: ShrinkPolicy,
+}
+
+impl<T: Default + Clone, const N: usize> ShrinkingVec<T, N> {
+    pub fn new(vec: LocalStorageVec<T, N>, shrink_policy: ShrinkPolicy) -> Self {
+        Self { vec, shrink_policy }
+    }
+
+    pub fn into_inner(self) -> LocalStorageVec<T, N> {
+        self.vec
+    }
+
+    /// The policy deciding when the vec moves back from the heap to the stack
+    pub fn shrink_policy(&self) -> ShrinkPolicy {
+        self.shrink_policy
+    }
+
+    /// Set the policy deciding when the vec moves back from the heap to the stack.
+    /// The policy is only applied by later operations, so a heap vec that already fits
+    /// on the stack stays on the heap until then
+    pub fn set_shrink_policy(&mut self, shrink_policy: ShrinkPolicy) {
+        self.shrink_policy = shrink_policy;
+    }
+
+    /// Set whether a heap vec moves back to the stack as soon as its elements fit after `pop`,
+    /// `remove` or `truncate`. Off by default. This is a shorthand for setting the shrink
+    /// policy to [`ShrinkPolicy::Always`] or [`ShrinkPolicy::Never`]
+    pub fn set_auto_shrink(&mut self, auto_shrink: bool) {
+        self.set_shrink_policy(if auto_shrink { ShrinkPolicy::Always } else { ShrinkPolicy::Never });
+    }
+
+    pub fn push(&mut self, elem: T) {
+        self.vec.push(elem);
+    }
+
+    pub fn insert(&mut self, index: usize, elem: T) {
+        self.vec.insert(index, elem);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let elem = self.vec.pop();
+        self.shrink_by_policy();
+        elem
+    }
+
+    #[track_caller]
+    pub fn remove(&mut self, index: usize) -> T {
+        let elem = self.vec.remove(index);
+        self.shrink_by_policy();
+        elem
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        self.vec.truncate(new_len);
+        self.shrink_by_policy();
+    }
+
+    pub fn clear(&mut self) {
+        self.vec.clear();
+        if self.shrink_policy != ShrinkPolicy::Never {
+            self.vec.shrink_to_fit();
+        }
+    }
+
+    /// Move back to the stack if the policy is `Always` and the elements fit
+    fn shrink_by_policy(&mut self) {
+        if self.shrink_policy == ShrinkPolicy::Always && self.vec.len() <= N {
+            self.vec.shrink_to_fit();
+        }
+    }
+}
+
+impl<T, const N: usize> From<LocalStorageVec<T, N>> for ShrinkingVec<T, N> {
+    fn from(vec: LocalStorageVec<T, N>) -> Self {
+        Self { vec, shrink_policy: ShrinkPolicy::Never }
+    }
+}
+
+impl<T, const N: usize> Deref for ShrinkingVec<T, N> {
+    type Target = LocalStorageVec<T, N>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+pub struct LocalStorageVecIterator<'a, T:'a, const N: usize> {
+    data: &'a[T],
+    index: usize,
+}
+
+impl<'a, T: Default + Clone, const N: usize> IntoIterator for &'a LocalStorageVec<T, N> {
+    type Item = T;
+    type IntoIter = LocalStorageVecIterator<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Self::IntoIter {
+            data: self.as_ref(),
+            index: 0,
+        }
+    }
+}
+
+impl<T: Default + Clone, const N: usize> Iterator for LocalStorageVecIterator<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.data.len() {
+            None
+        } else {
+            let output = self.data[self.index].clone();
+            self.index += 1;
+            Some(output)
+        }
+    }
+}
+
+/// An iterator moving the elements out of a `LocalStorageVec`.
+/// Elements that have not been yielded are dropped along with the iterator
+pub struct LocalStorageVecIntoIter<T, const N: usize> {
+    inner: IntoIterInner<T, N>,
+}
+
+enum IntoIterInner<T, const N: usize> {
+    // Yields only the first `len` elements, but owns (and drops) the whole buffer
+    Stack(Take<array::IntoIter<T, N>>),
+    Heap(vec::IntoIter<T>),
+}
+
+impl<T, const N: usize> IntoIterator for LocalStorageVec<T, N> {
+    type Item = T;
+    type IntoIter = LocalStorageVecIntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let inner = match self {
+            LocalStorageVec::Stack { buf, len } => IntoIterInner::Stack(buf.into_iter().take(len)),
+            LocalStorageVec::Heap(vec) => IntoIterInner::Heap(vec.into_iter()),
+        };
+        LocalStorageVecIntoIter { inner }
+    }
+}
+
+impl<T, const N: usize> Iterator for LocalStorageVecIntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            IntoIterInner::Stack(iter) => iter.next(),
+            IntoIterInner::Heap(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            IntoIterInner::Stack(iter) => iter.size_hint(),
+            IntoIterInner::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for LocalStorageVecIntoIter<T, N> {}
+
+impl<T: Default + Clone, const N: usize> Index<usize> for LocalStorageVec<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.as_ref()[index]
+    }
+}
+
+impl<T: Default + Clone, const N: usize> Index<RangeTo<usize>> for LocalStorageVec<T, N> {
+    type Output = [T];
+
+    fn index(&self, index: RangeTo<usize>) -> &Self::Output {
+        &self.as_ref()[index]
+    }
+}
+
+impl<T: Default + Clone, const N: usize> Index<RangeFrom<usize>> for LocalStorageVec<T, N> {
+    type Output = [T];
+
+    fn index(&self, index: RangeFrom<usize>) -> &Self::Output {
+        &self.as_ref()[index]
+    }
+}
+
+impl<T: Default + Clone, const N: usize> Index<Range<usize>> for LocalStorageVec<T, N> {
+    type Output = [T];
+
+    fn index(&self, index: Range<usize>) -> &Self::Output {
+        &self.as_ref()[index]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{LocalStorageVec, ShrinkPolicy, ShrinkingVec};
+    use std::collections::BTreeSet;
+
+    #[test]
+    // Don't remove the #[ignore] attribute or your tests will take forever!
+    #[ignore = "This test is just to validate the definition of `LocalStorageVec`. If it compiles, all is OK"]
+    #[allow(unreachable_code, unused_variables, clippy::empty_loop)]
+    fn it_compiles() {
+        // Here's a trick to 'initialize' a type while not actually
+        // creating a value: an infinite `loop` expression diverges
+        // and evaluates to the 'never type' `!`, which, as is can never
+        // actually be instantiated, coerces to any other type.
+        // Some other ways of diverging are by calling the `panic!` or the `todo!`
+        // macros.
+        // More info:
+        // - https://doc.rust-lang.org/rust-by-example/fn/diverging.html
+        // - https://doc.rust-lang.org/reference/expressions/loop-expr.html#infinite-loops
+        let vec: LocalStorageVec<u32, 10> = loop {};
+        match vec {
+            LocalStorageVec::Stack { buf, len } => {
+                let _buf: [u32; 10] = buf;
+                let _len: usize = len;
+            }
+            LocalStorageVec::Heap(v) => {
+                let _v: Vec<u32> = v;
+            }
+        }
+    }
+
+    // Uncomment me for part B
+    #[test]
+    fn it_from_vecs() {
+        // The `vec!` macro creates a `Vec<T>` in a way that resembles
+        // array-initialization syntax.
+        let vec: LocalStorageVec<usize, 10> = LocalStorageVec::from(vec![1, 2, 3]);
+        // Assert that the call to `from` indeed yields a `Heap` variant
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+
+        let vec: LocalStorageVec<usize, 2> = LocalStorageVec::from(vec![1, 2, 3]);
+
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+    }
+
+    // Uncomment me for part C
+    #[test]
+    fn it_as_refs() {
+        let vec: LocalStorageVec<i32, 256> = LocalStorageVec::from([0; 128]);
+        let slice: &[i32] = vec.as_ref();
+        assert!(slice.len() == 128);
+        let vec: LocalStorageVec<i32, 32> = LocalStorageVec::from([0; 128]);
+        let slice: &[i32] = vec.as_ref();
+        assert!(slice.len() == 128);
+
+        let mut vec: LocalStorageVec<i32, 256> = LocalStorageVec::from([0; 128]);
+        let slice_mut: &[i32] = vec.as_mut();
+        assert!(slice_mut.len() == 128);
+        let mut vec: LocalStorageVec<i32, 32> = LocalStorageVec::from([0; 128]);
+        let slice_mut: &[i32] = vec.as_mut();
+        assert!(slice_mut.len() == 128);
+    }
+
+    // Uncomment me for part D
+    #[test]
+    fn it_constructs() {
+        let vec: LocalStorageVec<usize, 10> = LocalStorageVec::new();
+        // Assert that the call to `new` indeed yields a `Stack` variant with zero length
+        assert!(matches!(vec, LocalStorageVec::Stack { buf: _, len: 0 }));
+    }
+
+    // Uncomment me for part D
+    #[test]
+    fn it_lens() {
+        let vec: LocalStorageVec<_, 3> = LocalStorageVec::from([0, 1, 2]);
+        assert_eq!(vec.len(), 3);
+        let vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 2]);
+        assert_eq!(vec.len(), 3);
+    }
+
+    // Uncomment me for part D
+    #[test]
+    fn it_pushes() {
+        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::new();
+        for value in 0..128 {
+            vec.push(value);
+        }
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 128, .. }));
+        for value in 128..256 {
+            vec.push(value);
+        }
+        assert!(matches!(vec, LocalStorageVec::Heap(v) if v.len() == 256))
+    }
+
+    // Uncomment me for part D
+    #[test]
+    fn it_pops() {
+        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 128]);
+        for _ in 0..128 {
+            assert_eq!(vec.pop(), Some(0))
+        }
+        assert_eq!(vec.pop(), None);
+
+        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 256]);
+        for _ in 0..256 {
+            assert_eq!(vec.pop(), Some(0))
+        }
+        assert_eq!(vec.pop(), None);
+
+        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::from(vec![0; 256]);
+        for _ in 0..256 {
+            assert_eq!(vec.pop(), Some(0))
+        }
+        assert_eq!(vec.pop(), None);
+    }
+
+    // Uncomment me for part D
+    #[test]
+    fn it_inserts() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        vec.insert(1, 3);
+        assert!(matches!(
+            vec,
+            LocalStorageVec::Stack {
+                buf: [0, 3, 1, 2],
+                len: 4
+            }
+        ));
+
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2, 3]);
+        vec.insert(1, 3);
+        assert!(matches!(vec, LocalStorageVec::Heap { .. }));
+        assert_eq!(vec.as_ref(), &[0, 3, 1, 2, 3]);
+
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2, 3, 4]);
+        vec.insert(1, 3);
+        assert!(matches!(vec, LocalStorageVec::Heap { .. }));
+        assert_eq!(vec.as_ref(), &[0, 3, 1, 2, 3, 4])
+    }
+
+    // Uncomment me for part D
+    #[test]
+    fn it_removes() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        let elem = vec.remove(1);
+        dbg!(&vec);
+        assert!(matches!(
+            vec,
+            LocalStorageVec::Stack {
+                buf: [0, 2, _, _],
+                len: 2
+            }
+        ));
+        assert_eq!(elem, 1);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 2]);
+        let elem = vec.remove(1);
+        assert!(matches!(vec, LocalStorageVec::Heap(..)));
+        assert_eq!(vec.as_ref(), &[0, 2]);
+        assert_eq!(elem, 1);
+    }
+
+    // Uncomment me for part D
+    #[test]
+    fn it_clears() {
+        let mut vec: LocalStorageVec<_, 10> = LocalStorageVec::from([0, 1, 2, 3]);
+        assert!(matches!(vec, LocalStorageVec::Stack { buf: _, len: 4 }));
+        vec.clear();
+        assert_eq!(vec.len(), 0);
+
+        let mut vec: LocalStorageVec<_, 3> = LocalStorageVec::from([0, 1, 2, 3]);
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+        vec.clear();
         assert_eq!(vec.len(), 0);
     }
 
-    // Uncomment me for part E
+    // Uncomment me for part E
+    #[test]
+    fn it_iters() {
+        let vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 32]);
+        let mut iter = vec.into_iter();
+        for item in &mut iter {
+            assert_eq!(item, 0);
+        }
+        assert_eq!(iter.next(), None);
+
+        let vec: LocalStorageVec<_, 128> = LocalStorageVec::from(vec![0; 128]);
+        let mut iter = vec.into_iter();
+        for item in &mut iter {
+            assert_eq!(item, 0);
+        }
+        assert_eq!(iter.next(), None);
+    }
+
+    // Uncomment me for part F
+    #[test]
+    fn it_indexes() {
+        let vec: LocalStorageVec<i32, 10> = LocalStorageVec::from([0, 1, 2, 3, 4, 5]);
+        assert_eq!(vec[1], 1);
+        assert_eq!(vec[..2], [0, 1]);
+        assert_eq!(vec[4..], [4, 5]);
+        assert_eq!(vec[1..3], [1, 2]);
+    }
+
+    // Uncomment me for part H
+    #[test]
+    fn it_borrowing_iters() {
+        let vec: LocalStorageVec<String, 10> = LocalStorageVec::from([
+            "0".to_owned(),
+            "1".to_owned(),
+            "2".to_owned(),
+            "3".to_owned(),
+            "4".to_owned(),
+            "5".to_owned(),
+        ]);
+        let iter = vec.iter();
+        for _ in iter {}
+        // This requires the `vec` not to be consumed by the call to `iter()`
+        drop(vec);
+    }
+
+    // Uncomment me for part J
+    #[test]
+    #[allow(unused_variables)]
+    fn it_derefs() {
+        use std::ops::{Deref, DerefMut};
+        let vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 128]);
+        // `chunks` is a method that's defined for slices `[T]`, that we can use thanks to `Deref`
+        let chunks = vec.chunks(4);
+        let slice: &[_] = vec.deref();
+
+        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 128]);
+        let chunks = vec.chunks_mut(4);
+        let slice: &mut [_] = vec.deref_mut();
+    }
+
+    #[test]
+    fn it_collects() {
+        let vec: LocalStorageVec<_, 4> = (0..3).collect();
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 3, .. }));
+        assert_eq!(vec.as_ref(), &[0, 1, 2]);
+
+        let vec: LocalStorageVec<_, 4> = (0..6).collect();
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+        assert_eq!(vec.as_ref(), &[0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn it_collects_on_heap() {
+        let vec: LocalStorageVec<_, 4> = LocalStorageVec::collect_on_heap([1]);
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+        assert_eq!(vec.as_ref(), &[1]);
+    }
+
+    #[test]
+    fn it_truncates() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        vec.truncate(1);
+        assert_eq!(vec.as_ref(), &[0]);
+        vec.truncate(2);
+        assert_eq!(vec.as_ref(), &[0]);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 2]);
+        vec.truncate(1);
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+        assert_eq!(vec.as_ref(), &[0]);
+    }
+
+    #[test]
+    fn it_shrinks_to_fit() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::new();
+        for value in 0..6 {
+            vec.push(value);
+        }
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+
+        vec.pop();
+        vec.shrink_to_fit();
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+
+        vec.pop();
+        vec.shrink_to_fit();
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 4, .. }));
+        assert_eq!(vec.as_ref(), &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn it_zips_with() {
+        let a: LocalStorageVec<_, 4> = LocalStorageVec::from([1, 2, 3]);
+        let b: LocalStorageVec<_, 4> = LocalStorageVec::from([10, 20, 30]);
+        let sum = a.zip_with(&b, |a, b| a + b);
+        assert_eq!(sum.as_ref(), &[11, 22, 33]);
+
+        let b: LocalStorageVec<_, 4> = LocalStorageVec::from([10, 20, 30, 40, 50]);
+        let sum = a.zip_with(&b, |a, b| a + b);
+        assert_eq!(sum.as_ref(), &[11, 22, 33]);
+        let sum = b.zip_with(&a, |b, a| a + b);
+        assert_eq!(sum.as_ref(), &[11, 22, 33]);
+    }
+
+    #[test]
+    fn it_into_vecs() {
+        let vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        assert_eq!(vec.into_vec(), vec![0, 1, 2]);
+
+        let vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 2]);
+        assert_eq!(vec.into_vec(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn it_flattens() {
+        let nested: LocalStorageVec<LocalStorageVec<_, 2>, 4> = LocalStorageVec::from([
+            LocalStorageVec::from([1, 2]),
+            LocalStorageVec::from([3]),
+            LocalStorageVec::from([4, 5]),
+        ]);
+        let flat = nested.flatten();
+        assert!(matches!(flat, LocalStorageVec::Heap(_)));
+        assert_eq!(flat.as_ref(), &[1, 2, 3, 4, 5]);
+
+        let nested: LocalStorageVec<LocalStorageVec<i32, 2>, 4> = LocalStorageVec::new();
+        assert!(nested.flatten().is_empty());
+    }
+
+    #[test]
+    fn it_dedups_sorted() {
+        let mut vec: LocalStorageVec<_, 10> = LocalStorageVec::from([1, 1, 2, 3, 3, 3, 4, 5, 5]);
+        vec.dedup_sorted();
+        assert_eq!(vec.as_ref(), &[1, 2, 3, 4, 5]);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([1, 1, 1, 2, 2]);
+        vec.dedup_sorted();
+        assert_eq!(vec.as_ref(), &[1, 2]);
+
+        let mut vec: LocalStorageVec<_, 10> = LocalStorageVec::from([1, 1, 2, 1]);
+        vec.dedup_sorted();
+        assert_eq!(vec.as_ref(), &[1, 2, 1]);
+    }
+
+    #[test]
+    fn it_clones_into_heap() {
+        let vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        let clone = vec.clone_into_heap();
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 3, .. }));
+        assert!(matches!(clone, LocalStorageVec::Heap(_)));
+        assert_eq!(clone.as_ref(), vec.as_ref());
+    }
+
+    #[test]
+    fn it_takes() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        let taken = vec.take();
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 0, .. }));
+        assert_eq!(taken.as_ref(), &[0, 1, 2]);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 2]);
+        let taken = vec.take();
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 0, .. }));
+        assert!(matches!(taken, LocalStorageVec::Heap(_)));
+        assert_eq!(taken.as_ref(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn it_updates() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        assert!(vec.update(1, |elem| *elem += 10));
+        assert_eq!(vec.as_ref(), &[0, 11, 2]);
+
+        assert!(!vec.update(3, |elem| *elem += 10));
+        assert_eq!(vec.as_ref(), &[0, 11, 2]);
+    }
+
+    #[test]
+    fn it_fills_from_iter() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0, 1, 2, 3, 4]);
+        vec.fill_from_iter([10, 11, 12]);
+        assert_eq!(vec.as_ref(), &[10, 11, 12, 3, 4]);
+
+        vec.fill_from_iter(20..);
+        assert_eq!(vec.as_ref(), &[20, 21, 22, 23, 24]);
+        assert_eq!(vec.len(), 5);
+    }
+
+    #[test]
+    fn it_counts_heap_bytes() {
+        let mut vec: LocalStorageVec<u32, 4> = LocalStorageVec::from([0, 1, 2, 3]);
+        assert!(!vec.is_spilled());
+        assert_eq!(vec.heap_bytes(), 0);
+
+        vec.push(4);
+        assert!(vec.is_spilled());
+        assert!(vec.heap_bytes() >= 5 * std::mem::size_of::<u32>());
+    }
+
+    #[test]
+    fn it_pushes_ring() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::new();
+        for value in 0..7 {
+            vec.push_ring(value);
+        }
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 4, .. }));
+        assert_eq!(vec.as_ref(), &[3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn it_splits_first_chunk() {
+        let vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0, 1, 2, 3, 4]);
+        let (head, rest) = vec.split_first_chunk::<2>().unwrap();
+        assert_eq!(head, &[0, 1]);
+        assert_eq!(rest, &[2, 3, 4]);
+
+        let vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0]);
+        assert!(vec.split_first_chunk::<2>().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 5) should be < len (is 3)")]
+    fn it_panics_on_remove_out_of_range() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        vec.remove(5);
+    }
+
+    #[test]
+    fn it_reports_remove_panic_at_call_site() {
+        // Run the test above in a child process, whose stderr holds the panic message
+        // with its location, rather than swapping the panic hook of this one
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "test::it_panics_on_remove_out_of_range", "--nocapture"])
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(output.status.success(), "{stderr}");
+
+        let location = stderr.split("panicked at ").nth(1).expect(&stderr);
+        let mut location = location.split(':');
+        assert_eq!(location.next(), Some(file!()));
+        let line: usize = location.next().unwrap().parse().unwrap();
+        assert_eq!(include_str!("lib.rs").lines().nth(line - 1), Some("        vec.remove(5);"));
+    }
+
+    #[test]
+    fn it_removes_from_full_stack() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2, 3]);
+        assert_eq!(vec.remove(3), 3);
+        assert_eq!(vec.remove(0), 0);
+        assert_eq!(vec.as_ref(), &[1, 2]);
+    }
+
+    #[test]
+    fn it_borrows_as_array() {
+        let vec: LocalStorageVec<_, 3> = LocalStorageVec::from([0, 1, 2]);
+        assert_eq!(vec.as_array_ref(), Some(&[0, 1, 2]));
+
+        let vec: LocalStorageVec<_, 3> = LocalStorageVec::from([0, 1]);
+        assert_eq!(vec.as_array_ref(), None);
+
+        let vec: LocalStorageVec<_, 3> = LocalStorageVec::from(vec![0, 1, 2]);
+        assert_eq!(vec.as_array_ref(), None);
+    }
+
+    #[test]
+    fn it_removes_all() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from([10, 11, 12, 13, 14]);
+        assert_eq!(vec.remove_all(&[1, 3]), vec![11, 13]);
+        assert_eq!(vec.as_ref(), &[10, 12, 14]);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([10, 11, 12, 13, 14]);
+        assert_eq!(vec.remove_all(&[0, 4]), vec![10, 14]);
+        assert_eq!(vec.as_ref(), &[11, 12, 13]);
+
+        assert_eq!(vec.remove_all(&[]), Vec::<i32>::new());
+        assert_eq!(vec.as_ref(), &[11, 12, 13]);
+    }
+
+    #[test]
+    #[should_panic(expected = "removal indices should be sorted and unique")]
+    fn it_panics_on_unsorted_remove_all() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from([10, 11, 12, 13, 14]);
+        vec.remove_all(&[3, 1]);
+    }
+
+    #[test]
+    fn it_folds_chunks() {
+        let vec: LocalStorageVec<_, 16> = LocalStorageVec::from([1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(vec.chunks_fold(3, 0, |acc, chunk| acc + chunk.iter().sum::<i32>()), vec![6, 15, 24]);
+        assert_eq!(vec.chunks_fold(4, 0, |acc, chunk| acc + chunk.iter().sum::<i32>()), vec![10, 26, 9]);
+        assert_eq!(vec.chunks_fold(3, 100, |acc, chunk| acc + chunk.iter().sum::<i32>()), vec![106, 115, 124]);
+        assert_eq!(vec.chunks_fold(4, vec![0], |mut acc, chunk| { acc.extend(chunk); acc }), vec![
+            vec![0, 1, 2, 3, 4],
+            vec![0, 5, 6, 7, 8],
+            vec![0, 9],
+        ]);
+    }
+
+    #[test]
+    fn it_strides() {
+        let vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0, 1, 2, 3, 4, 5]);
+        assert_eq!(vec.stride(2).copied().collect::<Vec<_>>(), vec![0, 2, 4]);
+        assert_eq!(vec.stride(4).copied().collect::<Vec<_>>(), vec![0, 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_on_zero_stride() {
+        let vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0, 1, 2]);
+        let _ = vec.stride(0);
+    }
+
+    #[test]
+    fn it_into_boxed_slices() {
+        let vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0, 1, 2]);
+        let boxed = vec.into_boxed_slice();
+        assert_eq!(boxed.len(), 3);
+        assert_eq!(&*boxed, &[0, 1, 2]);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 2]);
+        vec.pop();
+        let boxed = vec.into_boxed_slice();
+        assert_eq!(boxed.len(), 2);
+        assert_eq!(&*boxed, &[0, 1]);
+    }
+
+    #[test]
+    fn it_iterates_mutable_windows() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0, 4, 8, 12]);
+        vec.for_each_window_mut(2, |window| window[1] = (window[0] + window[1]) / 2);
+        assert_eq!(vec.as_ref(), &[0, 2, 5, 8]);
+
+        let mut calls = 0;
+        vec.for_each_window_mut(5, |_| calls += 1);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn it_inserts_sorted() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([1, 3, 5]);
+        assert_eq!(vec.insert_sorted(0), 0);
+        assert_eq!(vec.insert_sorted(4), 3);
+        assert_eq!(vec.insert_sorted(6), 5);
+        assert_eq!(vec.as_ref(), &[0, 1, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn it_finds_partition_point() {
+        let vec: LocalStorageVec<_, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        assert_eq!(vec.partition_point(|x| *x < 3), 2);
+        assert_eq!(vec.partition_point(|x| *x < 10), 5);
+        assert_eq!(vec.partition_point(|x| *x < 0), 0);
+    }
+
+    #[test]
+    fn it_dedups_keeping_last() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from([1, 1, 2, 3, 3]);
+        vec.dedup_keep_last();
+        assert_eq!(vec.as_ref(), &[1, 2, 3]);
+        assert_eq!(vec.len(), 3);
+
+        /// An update to the value with the given `id`
+        #[derive(Debug, Default, Clone)]
+        struct Update {
+            id: u32,
+            value: &'static str,
+        }
+
+        impl PartialEq for Update {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([
+            Update { id: 1, value: "a" },
+            Update { id: 1, value: "b" },
+            Update { id: 2, value: "c" },
+            Update { id: 1, value: "d" },
+        ]);
+        vec.dedup_keep_last();
+        let values: Vec<_> = vec.as_ref().iter().map(|update| update.value).collect();
+        assert_eq!(values, vec!["b", "c", "d"]);
+    }
+
+    #[test]
+    fn it_retains_indices() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from([10, 11, 12, 13, 14, 15]);
+        vec.retain_indices(&BTreeSet::from([0, 2, 4]));
+        assert_eq!(vec.as_ref(), &[10, 12, 14]);
+        assert_eq!(vec.len(), 3);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([10, 11, 12]);
+        vec.retain_indices(&BTreeSet::from([1, 7]));
+        assert_eq!(vec.as_ref(), &[11]);
+    }
+
+    #[test]
+    fn it_compacts() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from(["", "a", "", "b"]);
+        vec.compact();
+        assert_eq!(vec.as_ref(), &["a", "b"]);
+        assert_eq!(vec.len(), 2);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([0, 1, 0, 0, 2]);
+        vec.compact();
+        assert_eq!(vec.as_ref(), &[1, 2]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_round_trips_ndjson() {
+        #[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Record {
+            id: u32,
+            message: String,
+        }
+
+        let vec: LocalStorageVec<_, 4> = LocalStorageVec::from([
+            Record { id: 1, message: "started".to_owned() },
+            Record { id: 2, message: "stopped".to_owned() },
+        ]);
+
+        let ndjson = vec.to_ndjson().unwrap();
+        assert_eq!(
+            ndjson,
+            "{\"id\":1,\"message\":\"started\"}\n{\"id\":2,\"message\":\"stopped\"}\n"
+        );
+
+        let parsed: LocalStorageVec<Record, 4> = LocalStorageVec::from_ndjson(&ndjson).unwrap();
+        assert_eq!(parsed.as_ref(), vec.as_ref());
+
+        assert!(LocalStorageVec::<Record, 4>::from_ndjson("{\"id\":1}").is_err());
+    }
+
+    #[test]
+    fn it_chunks_exact_with_remainder() {
+        let vec: LocalStorageVec<_, 16> = LocalStorageVec::from([0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let (chunks, remainder) = vec.chunks_exact_with_remainder(3);
+        assert_eq!(chunks.collect::<Vec<_>>(), vec![&[0, 1, 2], &[3, 4, 5], &[6, 7, 8]]);
+        assert_eq!(remainder, &[9]);
+
+        let (chunks, remainder) = vec.chunks_exact_with_remainder(5);
+        assert_eq!(chunks.count(), 2);
+        assert!(remainder.is_empty());
+    }
+
+    #[test]
+    fn it_maps_in_place() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([1, 2, 3]);
+        vec.map_in_place(|x| *x *= 2);
+        assert_eq!(vec.as_ref(), &[2, 4, 6]);
+
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::from([1, 2, 3]);
+        vec.map_in_place(|x| *x *= 2);
+        assert_eq!(vec.as_ref(), &[2, 4, 6]);
+    }
+
+    #[test]
+    fn it_drops_while_front() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0, 0, 1, 2, 0]);
+        vec.drop_while_front(|x| *x == 0);
+        assert_eq!(vec.as_ref(), &[1, 2, 0]);
+
+        vec.drop_while_front(|x| *x < 10);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn it_iters_circular() {
+        let vec: LocalStorageVec<_, 8> = LocalStorageVec::from(['a', 'b', 'c', 'd']);
+        assert_eq!(vec.iter_circular(2).copied().collect::<Vec<_>>(), vec!['c', 'd', 'a', 'b']);
+        assert_eq!(vec.iter_circular(0).copied().collect::<Vec<_>>(), vec!['a', 'b', 'c', 'd']);
+
+        let vec: LocalStorageVec<char, 8> = LocalStorageVec::new();
+        assert_eq!(vec.iter_circular(3).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "start (is 4) should be < len (is 4)")]
+    fn it_panics_on_iter_circular_out_of_range() {
+        let vec: LocalStorageVec<_, 8> = LocalStorageVec::from(['a', 'b', 'c', 'd']);
+        let _ = vec.iter_circular(4);
+    }
+
+    #[test]
+    fn it_tries_to_reserve() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1]);
+        assert!(vec.try_reserve(2).is_ok());
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 2, .. }));
+
+        assert!(vec.try_reserve(3).is_ok());
+        assert!(matches!(&vec, LocalStorageVec::Heap(v) if v.capacity() >= 5));
+        assert_eq!(vec.as_ref(), &[0, 1]);
+
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1]);
+        assert!(vec.try_reserve(usize::MAX).is_err());
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 2, .. }));
+        assert_eq!(vec.as_ref(), &[0, 1]);
+    }
+
+    #[test]
+    fn it_shift_inserts() {
+        let mut vec: LocalStorageVec<_, 4> = LocalStorageVec::from([0, 1, 2]);
+        vec.shift_insert(1, 10);
+        assert!(matches!(vec, LocalStorageVec::Stack { buf: [0, 10, 1, 2], len: 4 }));
+
+        vec.shift_insert(2, 20);
+        assert!(matches!(vec, LocalStorageVec::Heap(_)));
+        assert_eq!(vec.as_ref(), &[0, 10, 20, 1, 2]);
+
+        vec.shift_insert(5, 30);
+        assert_eq!(vec.as_ref(), &[0, 10, 20, 1, 2, 30]);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 4) should be <= len (is 3)")]
+    fn it_panics_on_shift_insert_out_of_range() {
+        let mut vec: LocalStorageVec<_, 8> = LocalStorageVec::from([0, 1, 2]);
+        vec.shift_insert(4, 10);
+    }
+
+    #[test]
+    fn it_peeks() {
+        let mut vec: LocalStorageVec<_, 2> = LocalStorageVec::new();
+        assert_eq!(vec.peek(), None);
+
+        for value in 0..3 {
+            vec.push(value);
+            assert_eq!(vec.peek(), Some(&value));
+        }
+
+        while let Some(&peeked) = vec.peek() {
+            assert_eq!(vec.pop(), Some(peeked));
+        }
+        assert_eq!(vec.pop(), None);
+    }
+
+    #[test]
+    fn it_drains_to_vec() {
+        let mut vec: LocalStorageVec<String, 4> = LocalStorageVec::from(["a".to_string(), "b".to_string()]);
+        assert_eq!(vec.drain_to_vec(), vec!["a", "b"]);
+        assert!(vec.is_empty());
+        assert!(vec.is_stack());
+        assert_eq!(vec.drain_to_vec(), Vec::<String>::new());
+
+        let mut vec: LocalStorageVec<String, 1> = LocalStorageVec::from(vec!["a".to_string(), "b".to_string()]);
+        assert!(vec.is_spilled());
+        assert_eq!(vec.drain_to_vec(), vec!["a", "b"]);
+        assert!(vec.is_empty());
+        assert!(vec.is_stack());
+
+        vec.push("c".to_string());
+        assert_eq!(vec.as_ref(), &["c"]);
+    }
+
+    #[test]
+    fn it_finds_position_max_by_key() {
+        let vec: LocalStorageVec<&str, 8> = LocalStorageVec::from(["a", "abc", "ab", "xyz", "b"]);
+        assert_eq!(vec.position_max_by_key(|s| s.len()), Some(3));
+        assert_eq!(vec.position_max_by_key(|s| std::cmp::Reverse(s.len())), Some(4));
+
+        let vec: LocalStorageVec<&str, 2> = LocalStorageVec::from(vec!["a", "abcd", "ab"]);
+        assert_eq!(vec.position_max_by_key(|s| s.len()), Some(1));
+
+        let vec: LocalStorageVec<&str, 2> = LocalStorageVec::new();
+        assert_eq!(vec.position_max_by_key(|s| s.len()), None);
+    }
+
+    #[test]
+    fn it_splits() {
+        let vec: LocalStorageVec<u8, 8> = LocalStorageVec::from([1, 0, 2, 3, 0, 4]);
+        let parts: Vec<&[u8]> = vec.split(|&b| b == 0).collect();
+        assert_eq!(parts, [&[1][..], &[2, 3], &[4]]);
+        let parts: Vec<&[u8]> = vec.rsplit(|&b| b == 0).collect();
+        assert_eq!(parts, [&[4][..], &[2, 3], &[1]]);
+
+        let vec: LocalStorageVec<u8, 2> = LocalStorageVec::from(vec![b'a', b'\n', b'b', b'\n']);
+        let lines: Vec<&[u8]> = vec.split(|&b| b == b'\n').collect();
+        assert_eq!(lines, [&b"a"[..], b"b", b""]);
+    }
+
+    #[test]
+    fn it_swap_removes_front() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4]);
+        assert_eq!(vec.swap_remove_front(), Some(1));
+        assert_eq!(vec.as_ref(), &[4, 2, 3]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 4]);
+        assert_eq!(vec.swap_remove_front(), Some(1));
+        assert_eq!(vec.as_ref(), &[4, 2, 3]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from([7]);
+        assert_eq!(vec.swap_remove_front(), Some(7));
+        assert_eq!(vec.swap_remove_front(), None);
+    }
+
+    #[test]
+    fn it_binary_searches_by_key() {
+        let vec: LocalStorageVec<(u32, &str), 4> =
+            LocalStorageVec::from(vec![(1, "one"), (3, "three"), (5, "five"), (8, "eight"), (13, "thirteen")]);
+        assert_eq!(vec.binary_search_by_key(&8, |&(id, _)| id), Ok(3));
+        assert_eq!(vec.binary_search_by_key(&4, |&(id, _)| id), Err(2));
+
+        let vec: LocalStorageVec<(u32, &str), 4> = LocalStorageVec::from([(2, "two"), (4, "four")]);
+        assert_eq!(vec.binary_search_by_key(&2, |&(id, _)| id), Ok(0));
+        assert_eq!(vec.binary_search_by_key(&9, |&(id, _)| id), Err(2));
+    }
+
+    #[test]
+    fn it_checked_pushes() {
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::new();
+        assert_eq!(vec.checked_push(1, 3), Ok(()));
+        assert_eq!(vec.checked_push(2, 3), Ok(()));
+        assert_eq!(vec.checked_push(3, 3), Ok(()));
+        assert_eq!(vec.checked_push(4, 3), Err(4));
+        assert_eq!(vec.as_ref(), &[1, 2, 3]);
+
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::new();
+        assert_eq!(vec.checked_push(1, 0), Err(1));
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn it_builds_strings() {
+        let vec: LocalStorageVec<char, 4> = LocalStorageVec::from(['h', 'é', 'l', 'l', 'o']);
+        assert_eq!(vec.to_string(), "héllo");
+        assert_eq!(format!("[{vec}]"), "[héllo]");
+
+        let vec: LocalStorageVec<u8, 8> = LocalStorageVec::from(*b"hello");
+        assert_eq!(vec.try_to_string().unwrap(), "hello");
+        let vec: LocalStorageVec<u8, 2> = LocalStorageVec::from("héllo".as_bytes().to_vec());
+        assert_eq!(vec.try_to_string().unwrap(), "héllo");
+
+        let vec: LocalStorageVec<u8, 8> = LocalStorageVec::from([b'a', 0xff, b'b']);
+        assert_eq!(vec.try_to_string().unwrap_err().valid_up_to(), 1);
+    }
+
     #[test]
-    fn it_iters() {
-        let vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 32]);
-        let mut iter = vec.into_iter();
-        for item in &mut iter {
-            assert_eq!(item, 0);
+    fn it_rotates_to_front() {
+        let mut vec: LocalStorageVec<char, 8> = LocalStorageVec::from(['a', 'b', 'c', 'd']);
+        vec.rotate_to_front(2);
+        assert_eq!(vec.as_ref(), &['c', 'd', 'a', 'b']);
+        vec.rotate_to_front(0);
+        assert_eq!(vec.as_ref(), &['c', 'd', 'a', 'b']);
+
+        let mut vec: LocalStorageVec<char, 2> = LocalStorageVec::from(vec!['a', 'b', 'c', 'd']);
+        vec.rotate_to_front(3);
+        assert_eq!(vec.as_ref(), &['d', 'a', 'b', 'c']);
+    }
+
+    #[test]
+    #[should_panic(expected = "rotation index (is 4) should be < len (is 4)")]
+    fn it_panics_rotating_out_of_range() {
+        let mut vec: LocalStorageVec<char, 8> = LocalStorageVec::from(['a', 'b', 'c', 'd']);
+        vec.rotate_to_front(4);
+    }
+
+    #[test]
+    fn it_try_maps() {
+        let vec: LocalStorageVec<&str, 4> = LocalStorageVec::from(["1", "22", "333"]);
+        let parsed = vec.try_map(str::parse::<u32>).unwrap();
+        assert_eq!(parsed.as_ref(), &[1, 22, 333]);
+
+        let mut calls = 0;
+        let vec: LocalStorageVec<&str, 2> = LocalStorageVec::from(vec!["1", "x", "3", "y"]);
+        let result = vec.try_map(|s| {
+            calls += 1;
+            s.parse::<u32>().map_err(|_| s)
+        });
+        assert_eq!(result.unwrap_err(), "x");
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn it_groups_by() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 1, 2, 2, 2, 3]);
+        assert_eq!(vec.group_by(|a, b| a == b), [&[1, 1][..], &[2, 2, 2], &[3]]);
+
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 1, 2, 0]);
+        assert_eq!(vec.group_by(|a, b| a < b), [&[1, 2, 3][..], &[1, 2], &[0]]);
+
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::new();
+        assert!(vec.group_by(|a, b| a == b).is_empty());
+    }
+
+    #[test]
+    fn it_swaps_ranges() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        vec.swap_ranges(0..2, 3..5);
+        assert_eq!(vec.as_ref(), &[4, 5, 3, 1, 2]);
+        vec.swap_ranges(3..5, 0..2);
+        assert_eq!(vec.as_ref(), &[1, 2, 3, 4, 5]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 4]);
+        vec.swap_ranges(1..2, 2..3);
+        assert_eq!(vec.as_ref(), &[1, 3, 2, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "should not overlap")]
+    fn it_panics_swapping_overlapping_ranges() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        vec.swap_ranges(0..3, 2..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "should be of equal length")]
+    fn it_panics_swapping_unequal_ranges() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        vec.swap_ranges(0..1, 2..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "should be within len (is 5)")]
+    fn it_panics_swapping_out_of_range() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        vec.swap_ranges(0..2, 4..6);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn it_views_as_bytes() {
+        let mut vec: LocalStorageVec<u32, 4> = LocalStorageVec::from([1, 0x0403_0201]);
+        assert_eq!(vec.as_bytes().len(), 4 * vec.len());
+        let expected: Vec<u8> = [1u32, 0x0403_0201].iter().flat_map(|n| n.to_ne_bytes()).collect();
+        assert_eq!(vec.as_bytes(), expected);
+        #[cfg(target_endian = "little")]
+        assert_eq!(vec.as_bytes(), &[1, 0, 0, 0, 1, 2, 3, 4]);
+
+        vec.as_bytes_mut().fill(0xff);
+        assert_eq!(vec.as_ref(), &[u32::MAX, u32::MAX]);
+
+        let vec: LocalStorageVec<u32, 1> = LocalStorageVec::from(vec![1, 2, 3]);
+        assert_eq!(vec.as_bytes().len(), 12);
+    }
+
+    #[test]
+    fn it_retains_collecting() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([5, 12, 3, 20, 7, 15]);
+        let removed = vec.retain_collecting(|n| *n <= 10);
+        assert_eq!(vec.as_ref(), &[5, 3, 7]);
+        assert_eq!(removed, vec![12, 20, 15]);
+
+        let mut vec: LocalStorageVec<String, 2> =
+            LocalStorageVec::from(vec!["a".to_string(), "bb".to_string(), "ccc".to_string()]);
+        let removed = vec.retain_collecting(|s| {
+            s.push('!');
+            s.len() < 4
+        });
+        assert_eq!(vec.as_ref(), &["a!", "bb!"]);
+        assert_eq!(removed, vec!["ccc!"]);
+    }
+
+    #[test]
+    fn it_round_trips_strings() {
+        let vec: LocalStorageVec<u8, 8> = LocalStorageVec::from_str_bytes("hello");
+        assert!(!vec.is_spilled());
+        assert_eq!(vec.try_into_string().unwrap(), "hello");
+
+        let vec: LocalStorageVec<u8, 4> = LocalStorageVec::from_str_bytes("hello");
+        assert!(vec.is_spilled());
+        assert_eq!(vec.try_into_string().unwrap(), "hello");
+
+        let vec: LocalStorageVec<u8, 8> = LocalStorageVec::from([b'a', 0xc3, 0x28]);
+        let error = vec.try_into_string().unwrap_err();
+        assert_eq!(error.utf8_error().valid_up_to(), 1);
+        assert_eq!(error.into_bytes(), vec![b'a', 0xc3, 0x28]);
+    }
+
+    #[test]
+    fn it_joins_with() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3]);
+        let joined = vec.join_with(0);
+        assert_eq!(joined.as_ref(), &[1, 0, 2, 0, 3]);
+        assert!(!joined.is_spilled());
+
+        let vec: LocalStorageVec<i32, 4> = LocalStorageVec::from([1, 2, 3]);
+        let joined = vec.join_with(0);
+        assert_eq!(joined.as_ref(), &[1, 0, 2, 0, 3]);
+        assert!(joined.is_spilled());
+
+        let vec: LocalStorageVec<i32, 4> = LocalStorageVec::from([1]);
+        assert_eq!(vec.join_with(0).as_ref(), &[1]);
+        assert!(LocalStorageVec::<i32, 4>::new().join_with(0).is_empty());
+    }
+
+    #[test]
+    fn it_retains_first_n() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4, 6, 8]);
+        vec.retain_first_n(2, |n| n % 2 == 0);
+        assert_eq!(vec.as_ref(), &[2, 4]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 4, 6, 8]);
+        vec.retain_first_n(10, |n| n % 2 == 0);
+        assert_eq!(vec.as_ref(), &[2, 4, 6, 8]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3]);
+        vec.retain_first_n(0, |_| true);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn it_chunks_into_owned_vecs() {
+        let vec: LocalStorageVec<i32, 4> = LocalStorageVec::from(vec![1, 2, 3, 4, 5, 6, 7]);
+        let chunks = vec.chunked(3);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].as_ref(), &[1, 2, 3]);
+        assert_eq!(chunks[1].as_ref(), &[4, 5, 6]);
+        assert_eq!(chunks[2].as_ref(), &[7]);
+        assert!(chunks.iter().all(|chunk| !chunk.is_spilled()));
+
+        let chunks = vec.chunked(5);
+        assert!(chunks[0].is_spilled());
+        assert_eq!(chunks[1].as_ref(), &[6, 7]);
+    }
+
+    #[test]
+    fn it_splits_off_front() {
+        let mut vec: LocalStorageVec<char, 8> = LocalStorageVec::from(['a', 'b', 'c', 'd']);
+        let front = vec.split_off_front(2);
+        assert_eq!(front.as_ref(), &['a', 'b']);
+        assert_eq!(vec.as_ref(), &['c', 'd']);
+
+        let mut vec: LocalStorageVec<String, 2> =
+            LocalStorageVec::from(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        assert!(vec.split_off_front(0).is_empty());
+        let front = vec.split_off_front(3);
+        assert_eq!(front.as_ref(), &["a", "b", "c"]);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "split index (is 5) should be <= len (is 4)")]
+    fn it_panics_splitting_off_front_out_of_range() {
+        let mut vec: LocalStorageVec<char, 8> = LocalStorageVec::from(['a', 'b', 'c', 'd']);
+        vec.split_off_front(5);
+    }
+
+    #[test]
+    fn it_reports_its_variant() {
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::new();
+        vec.push(1);
+        vec.push(2);
+        assert!(vec.is_stack());
+        assert!(!vec.is_heap());
+
+        vec.push(3);
+        assert!(!vec.is_stack());
+        assert!(vec.is_heap());
+    }
+
+    #[test]
+    fn it_iterates_array_chunks() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        let chunks: Vec<&[i32; 2]> = vec.array_chunks().collect();
+        assert_eq!(chunks, [&[1, 2], &[3, 4]]);
+
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(vec.array_chunks::<3>().map(|[a, b, c]| a + b + c).collect::<Vec<_>>(), [6, 15]);
+        assert_eq!(vec.array_chunks::<7>().count(), 0);
+    }
+
+    #[test]
+    fn it_counts_partitions() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        assert_eq!(vec.partition_counts(|x| *x % 2 == 0), (2, 3));
+
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 4, 5]);
+        assert_eq!(vec.partition_counts(|x| *x > 0), (5, 0));
+        assert_eq!(LocalStorageVec::<i32, 2>::new().partition_counts(|_| true), (0, 0));
+    }
+
+    #[test]
+    fn it_rchunks_exact() {
+        let vec: LocalStorageVec<i32, 4> = LocalStorageVec::from(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let chunks = vec.rchunks_exact(3);
+        assert_eq!(chunks.remainder(), &[0]);
+        assert_eq!(chunks.collect::<Vec<_>>(), [&[7, 8, 9][..], &[4, 5, 6], &[1, 2, 3]]);
+
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4]);
+        let chunks = vec.rchunks_exact(2);
+        assert!(chunks.remainder().is_empty());
+        assert_eq!(chunks.count(), 2);
+    }
+
+    #[test]
+    fn it_views_first_and_last_chunks() {
+        let vec: LocalStorageVec<u8, 8> = LocalStorageVec::from([1, 2, 3, 4, 5]);
+        assert_eq!(vec.first_chunk::<2>(), Some(&[1, 2]));
+        assert_eq!(vec.last_chunk::<3>(), Some(&[3, 4, 5]));
+        assert_eq!(vec.first_chunk::<5>(), Some(&[1, 2, 3, 4, 5]));
+        assert_eq!(vec.first_chunk::<6>(), None);
+        assert_eq!(vec.last_chunk::<6>(), None);
+
+        let vec: LocalStorageVec<u8, 2> = LocalStorageVec::from(vec![1, 2, 3]);
+        assert_eq!(vec.first_chunk::<3>(), Some(&[1, 2, 3]));
+        assert_eq!(vec.last_chunk::<1>(), Some(&[3]));
+        assert_eq!(vec.last_chunk::<4>(), None);
+    }
+
+    #[test]
+    fn it_retains_unique() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 1, 3, 2, 4]);
+        vec.retain_unique();
+        assert_eq!(vec.as_ref(), &[1, 2, 3, 4]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 1, 3, 2, 4]);
+        vec.retain_unique();
+        assert_eq!(vec.as_ref(), &[1, 2, 3, 4]);
+
+        let mut vec: LocalStorageVec<&str, 2> = LocalStorageVec::from(vec!["b", "a", "b", "b"]);
+        vec.retain_unique();
+        assert_eq!(vec.as_ref(), &["b", "a"]);
+    }
+
+    #[test]
+    fn it_pops_front_n() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4]);
+        assert_eq!(vec.pop_front_n(2), vec![1, 2]);
+        assert_eq!(vec.as_ref(), &[3, 4]);
+        assert_eq!(vec.pop_front_n(5), vec![3, 4]);
+        assert!(vec.is_empty());
+        assert_eq!(vec.pop_front_n(1), Vec::<i32>::new());
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 4]);
+        assert_eq!(vec.pop_front_n(3), vec![1, 2, 3]);
+        assert_eq!(vec.as_ref(), &[4]);
+    }
+
+    #[test]
+    fn it_moves_into_iter() {
+        use std::cell::Cell;
+
+        /// Counts how many times it is dropped, and implements neither `Clone` nor `Default`
+        struct DropCounter<'a>(u32, &'a Cell<usize>);
+
+        impl Drop for DropCounter<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
         }
-        assert_eq!(iter.next(), None);
 
-        let vec: LocalStorageVec<_, 128> = LocalStorageVec::from(vec![0; 128]);
+        let drops = Cell::new(0);
+        let counter = |n| DropCounter(n, &drops);
+
+        let vec: LocalStorageVec<DropCounter, 4> = LocalStorageVec::Stack {
+            buf: [counter(1), counter(2), counter(3), counter(4)],
+            len: 4,
+        };
         let mut iter = vec.into_iter();
-        for item in &mut iter {
-            assert_eq!(item, 0);
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next().map(|c| c.0), Some(1));
+        assert_eq!(iter.next().map(|c| c.0), Some(2));
+        assert_eq!(drops.get(), 2);
+        drop(iter);
+        assert_eq!(drops.get(), 4);
+
+        drops.set(0);
+        let vec: LocalStorageVec<DropCounter, 2> = LocalStorageVec::Heap(vec![counter(1), counter(2), counter(3)]);
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.next().map(|c| c.0), Some(1));
+        assert_eq!(drops.get(), 1);
+        drop(iter);
+        assert_eq!(drops.get(), 3);
+
+        let vec: LocalStorageVec<Box<dyn Fn() -> i32>, 2> = LocalStorageVec::Heap(vec![Box::new(|| 1), Box::new(|| 2)]);
+        let mut sum = 0;
+        for f in vec {
+            sum += f();
         }
-        assert_eq!(iter.next(), None);
+        assert_eq!(sum, 3);
+
+        let vec: LocalStorageVec<String, 4> = LocalStorageVec::from(["a".to_string(), "b".to_string()]);
+        assert_eq!(vec.into_iter().collect::<Vec<_>>(), vec!["a", "b"]);
     }
 
-    // Uncomment me for part F
     #[test]
-    fn it_indexes() {
-        let vec: LocalStorageVec<i32, 10> = LocalStorageVec::from([0, 1, 2, 3, 4, 5]);
-        assert_eq!(vec[1], 1);
-        assert_eq!(vec[..2], [0, 1]);
-        assert_eq!(vec[4..], [4, 5]);
-        assert_eq!(vec[1..3], [1, 2]);
+    fn it_iterates_pairs() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4]);
+        assert_eq!(vec.pairs().collect::<Vec<_>>(), [(&1, &2), (&2, &3), (&3, &4)]);
+
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 4, 9]);
+        assert_eq!(vec.pairs().map(|(a, b)| b - a).collect::<Vec<_>>(), [3, 5]);
+
+        assert_eq!(LocalStorageVec::<i32, 2>::new().pairs().count(), 0);
+        assert_eq!(LocalStorageVec::<i32, 2>::from([1]).pairs().count(), 0);
     }
 
-    // Uncomment me for part H
     #[test]
-    fn it_borrowing_iters() {
-        let vec: LocalStorageVec<String, 10> = LocalStorageVec::from([
-            "0".to_owned(),
-            "1".to_owned(),
-            "2".to_owned(),
-            "3".to_owned(),
-            "4".to_owned(),
-            "5".to_owned(),
-        ]);
-        let iter = vec.iter();
-        for _ in iter {}
-        // This requires the `vec` not to be consumed by the call to `iter()`
-        drop(vec);
+    fn it_maintains_len_invariant() {
+        let mut vec: LocalStorageVec<i32, 4> = LocalStorageVec::new();
+        let check = |vec: &LocalStorageVec<i32, 4>| {
+            if let LocalStorageVec::Stack { len, .. } = vec {
+                assert!(*len <= 4);
+            }
+        };
+
+        for i in 0..6 {
+            vec.push(i);
+            check(&vec);
+        }
+        vec.insert(0, -1);
+        vec.remove(3);
+        check(&vec);
+        while vec.pop().is_some() {
+            check(&vec);
+        }
+
+        vec.insert(0, 1);
+        vec.insert(1, 2);
+        vec.insert(0, 0);
+        assert_eq!(vec.remove(1), 1);
+        check(&vec);
+        assert_eq!(vec.as_ref(), &[0, 2]);
     }
 
-    // Uncomment me for part J
+    #[cfg(debug_assertions)]
     #[test]
-    fn it_derefs() {
-        use std::ops::{Deref, DerefMut};
-        let vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 128]);
-        // `chunks` is a method that's defined for slices `[T]`, that we can use thanks to `Deref`
-        let chunks = vec.chunks(4);
-        let slice: &[_] = vec.deref();
+    #[should_panic(expected = "insertion index (is 3) should be <= len (is 1)")]
+    fn it_panics_inserting_past_len_in_debug() {
+        let mut vec: LocalStorageVec<i32, 4> = LocalStorageVec::from([1]);
+        vec.insert(3, 2);
+    }
 
-        let mut vec: LocalStorageVec<_, 128> = LocalStorageVec::from([0; 128]);
-        let chunks = vec.chunks_mut(4);
-        let slice: &mut [_] = vec.deref_mut();
+    #[test]
+    fn it_resizes_with() {
+        let mut counter = 0;
+        let mut next = || {
+            counter += 1;
+            counter
+        };
+
+        let mut vec: LocalStorageVec<i32, 4> = LocalStorageVec::from([-1, -2]);
+        vec.resize_with(3, &mut next);
+        assert_eq!(vec.as_ref(), &[-1, -2, 1]);
+        assert!(vec.is_stack());
+        vec.resize_with(5, &mut next);
+        assert_eq!(vec.as_ref(), &[-1, -2, 1, 2, 3]);
+        assert!(vec.is_heap());
+
+        vec.resize_with(2, &mut next);
+        assert_eq!(vec.as_ref(), &[-1, -2]);
+        vec.resize_with(4, &mut next);
+        assert_eq!(vec.as_ref(), &[-1, -2, 4, 5]);
+
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([-1, -2]);
+        vec.resize_with(5, &mut next);
+        assert_eq!(vec.as_ref(), &[-1, -2, 6, 7, 8]);
+        vec.resize_with(2, &mut next);
+        assert_eq!(vec.as_ref(), &[-1, -2]);
+        assert!(vec.is_stack());
+    }
+
+    #[test]
+    fn it_iterates_overlapping_chunks() {
+        let vec: LocalStorageVec<i32, 4> = (0..8).collect();
+        let chunks: Vec<&[i32]> = vec.overlapping_chunks(3, 2).collect();
+        assert_eq!(chunks, [&[0, 1, 2][..], &[2, 3, 4], &[4, 5, 6]]);
+
+        assert!(vec.overlapping_chunks(1, 1).eq(vec.windows(1)));
+        assert!(vec.overlapping_chunks(3, 3).eq(vec.chunks_exact(3)));
+        assert_eq!(vec.overlapping_chunks(9, 1).count(), 0);
+
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([0, 1, 2]);
+        assert_eq!(vec.overlapping_chunks(2, 5).collect::<Vec<_>>(), [&[0, 1]]);
+    }
+
+    #[test]
+    fn it_modifies_ranges() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4, 5, 6]);
+        vec.modify_range(2..4, |n| *n *= 2);
+        assert_eq!(vec.as_ref(), &[1, 2, 6, 8, 5, 6]);
+        vec.modify_range(4.., |n| *n = 0);
+        assert_eq!(vec.as_ref(), &[1, 2, 6, 8, 0, 0]);
+        vec.modify_range(..=0, |n| *n += 10);
+        assert_eq!(vec.as_ref(), &[11, 2, 6, 8, 0, 0]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3]);
+        vec.modify_range(.., |n| *n = -*n);
+        assert_eq!(vec.as_ref(), &[-1, -2, -3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_modifying_out_of_range() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3]);
+        vec.modify_range(2..4, |n| *n *= 2);
+    }
+
+    #[test]
+    fn it_tries_for_each() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([2, 4, 5, 6, 7]);
+        let mut visited = 0;
+        let result = vec.try_for_each(|&n| {
+            visited += 1;
+            if n % 2 == 0 { Ok(()) } else { Err(n) }
+        });
+        assert_eq!(result, Err(5));
+        assert_eq!(visited, 3);
+
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![2, 4, 6]);
+        assert_eq!(vec.try_for_each(|&n| if n % 2 == 0 { Ok(()) } else { Err(n) }), Ok(()));
+    }
+
+    #[test]
+    fn it_selects_nth() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([5, 3, 1, 4, 2]);
+        let (smaller, median, larger) = vec.select_nth_unstable(2);
+        assert_eq!(*median, 3);
+        assert!(smaller.iter().all(|&n| n < 3));
+        assert!(larger.iter().all(|&n| n > 3));
+        assert_eq!(vec[2], 3);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![5, 3, 1, 4, 2]);
+        assert_eq!(*vec.select_nth_unstable(0).1, 1);
+        assert_eq!(*vec.select_nth_unstable(4).1, 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_selecting_out_of_range() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([5, 3, 1]);
+        vec.select_nth_unstable(3);
+    }
+
+    #[test]
+    fn it_finds_rposition() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 2, 1]);
+        assert_eq!(vec.rposition(|&n| n == 2), Some(3));
+        assert_eq!(vec.rposition(|&n| n == 4), None);
+
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 2, 1]);
+        assert_eq!(vec.rposition(|&n| n < 3), Some(4));
+    }
+
+    #[test]
+    fn it_dedups_by() {
+        let mut vec: LocalStorageVec<&str, 8> = LocalStorageVec::from(["a", "A", "b", "B", "b"]);
+        vec.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        assert_eq!(vec.as_ref(), &["a", "b"]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 4, 5, 7]);
+        vec.dedup_by(|a, b| *a - *b == 1);
+        assert_eq!(vec.as_ref(), &[1, 4, 7]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::new();
+        vec.dedup_by(|a, b| a == b);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn it_shrinks_by_policy() {
+        let mut vec: ShrinkingVec<i32, 2> = ShrinkingVec::from(LocalStorageVec::from(vec![1, 2, 3, 4]));
+        assert_eq!(vec.shrink_policy(), ShrinkPolicy::Never);
+        vec.pop();
+        vec.truncate(1);
+        assert!(vec.is_heap());
+        vec.clear();
+        assert!(vec.is_heap());
+
+        let mut vec = ShrinkingVec::new(LocalStorageVec::<i32, 2>::from(vec![1, 2, 3, 4]), ShrinkPolicy::OnClear);
+        vec.truncate(1);
+        assert_eq!(vec.remove(0), 1);
+        assert!(vec.is_heap());
+        vec.clear();
+        assert!(vec.is_stack());
+
+        let mut vec = ShrinkingVec::new(LocalStorageVec::<i32, 2>::from(vec![1, 2, 3, 4]), ShrinkPolicy::Always);
+        assert_eq!(vec.pop(), Some(4));
+        assert!(vec.is_heap());
+        assert_eq!(vec.remove(0), 1);
+        assert!(vec.is_stack());
+        assert_eq!(vec.as_ref(), &[2, 3]);
+        vec.push(4);
+        vec.insert(0, 1);
+        assert!(vec.is_heap());
+        vec.truncate(2);
+        assert!(vec.is_stack());
+        assert_eq!(vec.into_inner().as_ref(), &[1, 2]);
+
+        let mut vec: ShrinkingVec<i32, 2> = ShrinkingVec::from(LocalStorageVec::from(vec![1, 2, 3, 4]));
+        vec.pop();
+        vec.set_shrink_policy(ShrinkPolicy::Always);
+        assert!(vec.is_heap());
+        vec.pop();
+        assert!(vec.is_stack());
+        assert_eq!(ShrinkPolicy::default(), ShrinkPolicy::Never);
+    }
+
+    #[test]
+    fn it_copies_to_owned_vec() {
+        let vec: LocalStorageVec<u8, 8> = LocalStorageVec::from([1u8, 2, 3]);
+        assert_eq!(vec.to_owned_vec(), vec.as_ref());
+
+        let vec: LocalStorageVec<u8, 2> = LocalStorageVec::from(vec![4u8, 5, 6, 7]);
+        assert_eq!(vec.to_owned_vec(), vec.as_ref());
+    }
+
+    #[test]
+    fn it_retains_indexed() {
+        let mut vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([10, 11, 12, 13, 14]);
+        vec.retain_indexed(|index, _| index % 2 == 0);
+        assert_eq!(vec.as_ref(), &[10, 12, 14]);
+
+        let mut vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 4, 5, 6]);
+        vec.retain_indexed(|index, &n| index % 3 != 2 && n != 1);
+        assert_eq!(vec.as_ref(), &[2, 4, 5]);
+    }
+
+    #[test]
+    fn it_clears_and_shrinks() {
+        use std::rc::Rc;
+
+        let item = Rc::new(1);
+        let mut vec: LocalStorageVec<Rc<i32>, 2> = LocalStorageVec::from(vec![item.clone(); 4]);
+        assert_eq!(Rc::strong_count(&item), 5);
+        vec.clear_and_shrink();
+        assert!(matches!(vec, LocalStorageVec::Stack { len: 0, .. }));
+        assert_eq!(Rc::strong_count(&item), 1);
+
+        let mut vec: LocalStorageVec<Rc<i32>, 2> = LocalStorageVec::from([item.clone()]);
+        vec.clear_and_shrink();
+        assert!(vec.is_empty());
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn it_extends_bounded() {
+        let mut vec: LocalStorageVec<i32, 4> = LocalStorageVec::from([-2, -1]);
+        assert_eq!(vec.extend_bounded(0..100, 5), 3);
+        assert_eq!(vec.len(), 5);
+        assert_eq!(vec.as_ref(), &[-2, -1, 0, 1, 2]);
+
+        assert_eq!(vec.extend_bounded(0..100, 5), 0);
+        assert_eq!(vec.extend_bounded(0..100, 3), 0);
+        assert_eq!(vec.extend_bounded(0..2, 10), 2);
+        assert_eq!(vec.len(), 7);
+
+        let mut source = 0..100;
+        let mut vec: LocalStorageVec<i32, 4> = LocalStorageVec::new();
+        vec.extend_bounded(&mut source, 2);
+        assert_eq!(source.next(), Some(2));
+    }
+
+    #[test]
+    fn it_filter_maps_into() {
+        let vec: LocalStorageVec<String, 4> = ["1", "two", "3", "", "-4"].into_iter().map(String::from).collect();
+        let parsed = vec.filter_map_into(|s| s.parse::<i32>().ok());
+        assert!(parsed.is_stack());
+        assert_eq!(parsed.as_ref(), &[1, 3, -4]);
+
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::from([1, 2, 3]);
+        let none = vec.filter_map_into(|_| None::<u8>);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn it_advances_window() {
+        let signal = [3, 1, 4, 1, 5, 9, 2, 6];
+        let mut window: LocalStorageVec<i32, 4> = LocalStorageVec::from([3, 1, 4, 1]);
+
+        window.advance_window(2, &signal[4..6]);
+        assert_eq!(window.as_ref(), &[4, 1, 5, 9]);
+        assert!(window.is_stack());
+
+        window.advance_window(1, &signal[6..8]);
+        assert_eq!(window.as_ref(), &[1, 5, 9, 2, 6]);
+        assert!(window.is_heap());
+    }
+
+    #[test]
+    #[should_panic(expected = "window advance (is 3) should be <= len (is 2)")]
+    fn it_panics_advancing_window_too_far() {
+        let mut window: LocalStorageVec<i32, 4> = LocalStorageVec::from([1, 2]);
+        window.advance_window(3, &[]);
+    }
+
+    #[test]
+    fn it_folds() {
+        let vec: LocalStorageVec<i32, 2> = LocalStorageVec::from(vec![1, 2, 3, 4]);
+        assert_eq!(vec.fold(0, |sum, n| sum + n), 10);
+
+        let vec: LocalStorageVec<String, 4> = ["a", "b", "c"].into_iter().map(String::from).collect();
+        assert_eq!(vec.fold(String::new(), |acc, s| acc + s), "abc");
+        assert_eq!(LocalStorageVec::<i32, 4>::new().fold(7, |sum, n| sum + n), 7);
+    }
+
+    #[test]
+    fn it_describes() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3]);
+        assert_eq!(vec.describe(), "Stack(len=3, cap=8)");
+
+        let mut items = Vec::with_capacity(512);
+        items.extend(0..300);
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from(items);
+        assert_eq!(vec.describe(), "Heap(len=300, cap=512)");
+    }
+
+    #[test]
+    fn it_reserves_and_fills() {
+        let mut vec: LocalStorageVec<i32, 4> = LocalStorageVec::from([1, 2]);
+        vec.reserve_and_fill(2, 7);
+        assert!(vec.is_stack());
+        assert_eq!(vec.as_ref(), &[1, 2, 7, 7]);
+
+        let mut vec: LocalStorageVec<i32, 4> = LocalStorageVec::from([1, 2]);
+        vec.reserve_and_fill(5, 7);
+        assert!(vec.is_heap());
+        assert_eq!(vec.as_ref(), &[1, 2, 7, 7, 7, 7, 7]);
+
+        vec.reserve_and_fill(1, 8);
+        vec.reserve_and_fill(0, 9);
+        assert_eq!(vec.as_ref(), &[1, 2, 7, 7, 7, 7, 7, 8]);
+    }
+
+    #[test]
+    fn it_running_folds() {
+        let vec: LocalStorageVec<i32, 8> = LocalStorageVec::from([1, 2, 3, 4]);
+        assert_eq!(vec.running_fold(0, |sum, n| sum + n), vec![1, 3, 6, 10]);
+
+        let vec: LocalStorageVec<&str, 2> = LocalStorageVec::from(vec!["a", "b", "c"]);
+        assert_eq!(vec.running_fold(String::new(), |acc, s| acc.clone() + s), vec!["a", "ab", "abc"]);
+        assert!(LocalStorageVec::<i32, 2>::new().running_fold(0, |sum, n| sum + n).is_empty());
+    }
+
+    #[test]
+    fn it_writes_bytes() {
+        use std::io::Write;
+
+        let name = "ab";
+        let mut vec: LocalStorageVec<u8, 16> = LocalStorageVec::new();
+        write!(vec, "{}-{name}", 12).unwrap();
+        assert!(vec.is_stack());
+        assert_eq!(vec.as_ref(), b"12-ab");
+
+        write!(vec, " and then {:>5}", 42).unwrap();
+        vec.flush().unwrap();
+        assert!(vec.is_heap());
+        assert_eq!(vec.as_ref(), b"12-ab and then    42");
+    }
+
+    #[test]
+    fn it_auto_shrinks() {
+        let mut vec: ShrinkingVec<i32, 4> = ShrinkingVec::from(LocalStorageVec::from(vec![0, 1, 2, 3, 4, 5]));
+        vec.set_auto_shrink(true);
+        assert!(vec.is_heap());
+
+        assert_eq!(vec.pop(), Some(5));
+        assert!(vec.is_heap());
+        assert_eq!(vec.pop(), Some(4));
+        assert!(vec.is_stack());
+        assert_eq!(vec.as_ref(), &[0, 1, 2, 3]);
+
+        vec.set_auto_shrink(false);
+        vec.push(4);
+        vec.pop();
+        assert!(vec.is_heap());
     }
 }
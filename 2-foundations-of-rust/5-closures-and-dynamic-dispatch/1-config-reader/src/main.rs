@@ -1,73 +1,673 @@
-use std::path::PathBuf;
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+use std::str::FromStr;
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-/// An imaginary config file
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Config<'a> {
+/// An imaginary config file. Fields missing from the file are
+/// taken from `Config::default()`
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
     port: u16,
-    base_url: &'a str,
-    s3_path: &'a str,
-    database_url: &'a str,
+    base_url: String,
+    s3_path: String,
+    database_url: String,
+}
+
+/// Redacts the fields that may contain credentials, so configs can be logged safely
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("port", &self.port)
+            .field("base_url", &self.base_url)
+            .field("s3_path", &"***")
+            .field("database_url", &"***")
+            .finish()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            port: 8080,
+            base_url: String::new(),
+            s3_path: String::new(),
+            database_url: String::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Read the config file at `path`, and deserialize it using the
+    /// format indicated by its extension
+    pub fn from_path(path: &Path) -> Result<Config, Error> {
+        // Paths without a UTF-8 extension are reported as unsupported
+        let extension = path.extension().and_then(|o| o.to_str());
+        let deserializer = deserializer_for(extension)?;
+
+        let file_contents = std::fs::read_to_string(path).map_err(Error::Io)?;
+
+        deserializer.deserialize(file_contents.as_str())
+    }
+
+    /// Read a config from `reader`, and deserialize it using the passed format
+    pub fn from_reader<R: Read>(mut reader: R, format: &str) -> Result<Config, Error> {
+        let deserializer = deserializer_for(Some(format))?;
+
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents).map_err(Error::Io)?;
+
+        deserializer.deserialize(contents.as_str())
+    }
+
+    /// Load the config files at `paths` in order, where each file only needs to
+    /// contain some of the fields. Fields set by later files override the ones
+    /// set by earlier files, and fields not set by any file are taken from `Config::default()`
+    pub fn load_layered(paths: &[&Path]) -> Result<Config, Error> {
+        let mut config = Config::default();
+
+        for path in paths {
+            let layer: PartialConfig = load(path)?;
+            layer.apply_to(&mut config);
+        }
+
+        Ok(config)
+    }
+
+    /// Check that the values in the config make sense
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.port == 0 {
+            return Err(Error::Invalid { field: "port", reason: "must not be 0" });
+        }
+
+        let urls = [("base_url", &self.base_url), ("s3_path", &self.s3_path), ("database_url", &self.database_url)];
+        for (field, value) in urls {
+            if value.trim().is_empty() {
+                return Err(Error::Invalid { field, reason: "must not be empty" });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse the URL fields of the config, failing if any of them is malformed
+    #[cfg(feature = "url")]
+    pub fn parse_urls(&self) -> Result<ParsedUrls, Error> {
+        let parse = |field: &'static str, value: &str| {
+            url::Url::parse(value).map_err(|source| Error::Url { field, source })
+        };
+
+        Ok(ParsedUrls {
+            base_url: parse("base_url", &self.base_url)?,
+            database_url: parse("database_url", &self.database_url)?,
+        })
+    }
+
+    /// A JSON schema describing the fields of a `Config` and their types,
+    /// which can be used to validate config files before loading them
+    pub fn schema() -> &'static str {
+        r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "Config",
+  "type": "object",
+  "properties": {
+    "port": { "type": "integer", "minimum": 1, "maximum": 65535, "default": 8080 },
+    "base_url": { "type": "string", "default": "" },
+    "s3_path": { "type": "string", "default": "" },
+    "database_url": { "type": "string", "default": "" }
+  }
+}"#
+    }
+}
+
+/// A `Config` of which any field may be missing, used as a layer by `Config::load_layered`
+#[derive(Deserialize)]
+struct PartialConfig {
+    port: Option<u16>,
+    base_url: Option<String>,
+    s3_path: Option<String>,
+    database_url: Option<String>,
+}
+
+impl PartialConfig {
+    /// Override the fields of `config` that are set in this layer
+    fn apply_to(self, config: &mut Config) {
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(base_url) = self.base_url {
+            config.base_url = base_url;
+        }
+        if let Some(s3_path) = self.s3_path {
+            config.s3_path = s3_path;
+        }
+        if let Some(database_url) = self.database_url {
+            config.database_url = database_url;
+        }
+    }
+}
+
+/// The URL fields of a `Config`, parsed into structured `Url`s
+#[cfg(feature = "url")]
+#[derive(Debug)]
+pub struct ParsedUrls {
+    pub base_url: url::Url,
+    pub database_url: url::Url,
 }
 
 #[derive(Debug)]
 /// Config deserialization error
 pub enum Error {
-    /// Something went wrong deserializing JSON
-    Json(serde_json::Error),
+    /// Something went wrong deserializing JSON. If the error
+    /// concerns a specific field, `field` holds the path to it
+    Json {
+        source: serde_json::Error,
+        field: Option<String>,
+    },
     /// Something went wrong deserializing YAML
     Yaml(serde_yaml::Error),
+    /// Something went wrong deserializing TOML
+    Toml(toml::de::Error),
+    /// Something went wrong serializing TOML
+    TomlSer(toml::ser::Error),
+    /// Something went wrong reading the config file
+    Io(std::io::Error),
+    /// The format (or file extension) is not supported
+    UnsupportedFormat(Option<String>),
+    /// The format was not known, and the contents could not be deserialized
+    /// in any of the supported formats. Holds the error message for each format tried
+    AllFormatsFailed(Vec<(Format, String)>),
+    /// A field of the config has an invalid value
+    Invalid {
+        field: &'static str,
+        reason: &'static str,
+    },
+    /// A URL field of the config is malformed
+    #[cfg(feature = "url")]
+    Url {
+        field: &'static str,
+        source: url::ParseError,
+    },
 }
 
 trait DeserializeConfig {
     /// Deserialize the contents into a `Config`
-    fn deserialize<'a>(&self, contents: &'a str) -> Result<Config<'a>, Error>;
+    fn deserialize(&self, contents: &str) -> Result<Config, Error>;
+}
+
+/// Deserializes into any type implementing `Deserialize`, not just `Config`
+trait DeserializeInto {
+    /// Deserialize the contents into a `T`
+    fn deserialize<T: DeserializeOwned>(&self, contents: &str) -> Result<T, Error>;
+}
+
+impl<D: DeserializeInto> DeserializeConfig for D {
+    fn deserialize(&self, contents: &str) -> Result<Config, Error> {
+        DeserializeInto::deserialize(self, contents)
+    }
 }
 
 struct JsonDeserializer;
 struct YamlDeserializer;
+struct TomlDeserializer;
+
+impl DeserializeInto for JsonDeserializer {
+    fn deserialize<T: DeserializeOwned>(&self, contents: &str) -> Result<T, Error> {
+        let mut deserializer = serde_json::Deserializer::from_str(contents);
+
+        let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+            // Errors that do not concern a specific field have no known path segments
+            let path = error.path();
+            let field = path
+                .iter()
+                .any(|segment| !matches!(segment, serde_path_to_error::Segment::Unknown))
+                .then(|| path.to_string());
+            Error::Json { source: error.into_inner(), field }
+        })?;
 
-impl DeserializeConfig for JsonDeserializer {
-    fn deserialize<'a>(&self, contents: &'a str) -> Result<Config<'a>, Error> {
-        serde_json::from_str(contents).map_err(|error| Error::Json(error))
+        deserializer
+            .end()
+            .map_err(|source| Error::Json { source, field: None })?;
+
+        Ok(value)
     }
 }
 
-impl DeserializeConfig for YamlDeserializer {
-    fn deserialize<'a>(&self, contents: &'a str) -> Result<Config<'a>, Error> {
-        serde_yaml::from_str(contents).map_err(|error| Error::Yaml(error))
+impl DeserializeInto for YamlDeserializer {
+    fn deserialize<T: DeserializeOwned>(&self, contents: &str) -> Result<T, Error> {
+        serde_yaml::from_str(contents).map_err(Error::Yaml)
+    }
+}
+
+impl DeserializeInto for TomlDeserializer {
+    fn deserialize<T: DeserializeOwned>(&self, contents: &str) -> Result<T, Error> {
+        toml::from_str(contents).map_err(Error::Toml)
+    }
+}
+
+/// A supported config format
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    /// Get the deserializer for this format
+    fn deserializer(self) -> Box<dyn DeserializeConfig> {
+        match self {
+            Format::Json => Box::new(JsonDeserializer),
+            Format::Yaml => Box::new(YamlDeserializer),
+            Format::Toml => Box::new(TomlDeserializer),
+        }
+    }
+
+    /// Deserialize the contents into a `T` using this format
+    fn deserialize<T: DeserializeOwned>(self, contents: &str) -> Result<T, Error> {
+        match self {
+            Format::Json => DeserializeInto::deserialize(&JsonDeserializer, contents),
+            Format::Yaml => DeserializeInto::deserialize(&YamlDeserializer, contents),
+            Format::Toml => DeserializeInto::deserialize(&TomlDeserializer, contents),
+        }
+    }
+}
+
+/// Parse a format name or file extension
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            _ => Err(Error::UnsupportedFormat(Some(s.to_owned()))),
+        }
+    }
+}
+
+/// Get the deserializer for the passed format name or file extension
+fn deserializer_for(format: Option<&str>) -> Result<Box<dyn DeserializeConfig>, Error> {
+    let format: Format = format.ok_or(Error::UnsupportedFormat(None))?.parse()?;
+    Ok(format.deserializer())
+}
+
+/// Deserialize a config in an unknown format, by trying each supported format in turn.
+/// YAML is tried last, as it also accepts many documents meant to be in another format.
+/// An empty or comment-only document is a valid (default) TOML config, so `main` doesn't
+/// guess the format of stdin, but asks for `--format` instead
+#[allow(dead_code)]
+fn detect_and_deserialize(contents: &str) -> Result<Config, Error> {
+    let mut errors = Vec::new();
+
+    for format in [Format::Json, Format::Toml, Format::Yaml] {
+        match format.deserializer().deserialize(contents) {
+            Ok(config) => return Ok(config),
+            Err(e) => errors.push((format, format!("{e:?}"))),
+        }
+    }
+
+    Err(Error::AllFormatsFailed(errors))
+}
+
+/// Read the file at `path`, and deserialize it into a `T` using the
+/// format indicated by its extension
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let extension = path.extension().and_then(|o| o.to_str());
+    let format: Format = extension.ok_or(Error::UnsupportedFormat(None))?.parse()?;
+
+    let file_contents = std::fs::read_to_string(path).map_err(Error::Io)?;
+
+    format.deserialize(file_contents.as_str())
+}
+
+/// Serialize the fully resolved `config` in the passed format, e.g. to show
+/// operators the effective config after all layers and overrides are applied.
+/// Unlike the `Debug` output, the credentials are not redacted
+fn dump_effective(config: &Config, format: Format) -> Result<String, Error> {
+    match format {
+        Format::Json => serde_json::to_string_pretty(config).map_err(|source| Error::Json { source, field: None }),
+        Format::Yaml => serde_yaml::to_string(config).map_err(Error::Yaml),
+        Format::Toml => toml::to_string(config).map_err(Error::TomlSer),
     }
 }
 
-// TODO add some types that implement `DeserializeConfig`
+/// Load the config file at `path` only to check that it deserializes and passes
+/// `Config::validate`, e.g. to lint config files in CI
+fn validate_file(path: &Path) -> Result<(), Error> {
+    Config::from_path(path)?.validate()
+}
 
 fn main() {
-    let mut args = std::env::args();
-    // Unwrapping is OK here, as UTF-8 Strings can always be converted to PathBufs
-    let Some(path) = args.nth(1).map(|a| PathBuf::try_from(a).unwrap()) else {
+    let mut path = None;
+    let mut format = None;
+    let mut check = false;
+    let mut dump_format = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next(),
+            "--check" => check = true,
+            "--dump-config" => dump_format = args.next(),
+            _ => path = Some(arg),
+        }
+    }
+
+    if check {
+        let Some(path) = path.filter(|path| path != "-") else {
+            eprintln!("Please specify the path of the config to check");
+            std::process::exit(2);
+        };
+
+        match validate_file(Path::new(&path)) {
+            Ok(()) => println!("Config at {path} is valid"),
+            Err(e) => {
+                eprintln!("Config at {path} is invalid: {e:?}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Read from stdin if explicitly asked for with `-`, or if no path is given and stdin is piped
+    let read_stdin = match path.as_deref() {
+        Some("-") => true,
+        Some(_) => false,
+        None => !std::io::stdin().is_terminal(),
+    };
+
+    let config = if read_stdin {
+        let Some(format) = format else {
+            eprintln!("Please specify the format of the config on stdin with --format");
+            return;
+        };
+        Config::from_reader(std::io::stdin().lock(), &format)
+    } else if let Some(path) = path {
+        Config::from_path(Path::new(&path))
+    } else {
         eprintln!("Please specify the input path");
         return;
     };
-    // Unwrapping is Ok as `path` was created from UTF-8 string, and so is the extension
-    let extension = path.extension().map(|o| o.to_str().unwrap());
-    let file_contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
+
+    let config = match config {
+        Ok(config) => config,
         Err(e) => {
-            // `path` was created from an UTF-8 string, so can be converted to one
-            eprintln!("Error reading file at path {}: {}", path.to_str().unwrap(), e);
+            eprintln!("Error reading config: {e:?}");
             return;
         }
     };
 
-    let deserializer: Box<dyn DeserializeConfig> = match extension {
-        Some("json") => Box::new(JsonDeserializer {}),
-        Some("yaml") => Box::new(YamlDeserializer {}),
-        Some("yml") => Box::new(YamlDeserializer {}),
-        _ => panic!("Unsupported extension")
+    let Some(dump_format) = dump_format else {
+        println!("Config was: {config:?}");
+        return;
     };
 
-    let config = deserializer.deserialize(file_contents.as_str());
+    match dump_format.parse().and_then(|format| dump_effective(&config, format)) {
+        Ok(dump) => println!("{}", dump.trim_end()),
+        Err(e) => eprintln!("Error dumping config: {e:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema() {
+        let schema = Config::schema();
+        for field in ["port", "base_url", "s3_path", "database_url"] {
+            assert!(schema.contains(&format!("\"{field}\"")), "Schema does not mention {field}");
+        }
+
+        let schema: serde_json::Value = serde_json::from_str(schema).unwrap();
+        assert_eq!(schema["properties"]["port"]["type"], "integer");
+        assert_eq!(schema["properties"]["port"]["minimum"], 1);
+        assert_eq!(schema["properties"]["port"]["default"], Config::default().port);
+    }
+
+    #[test]
+    fn test_debug_redacted() {
+        let config = Config::from_path(Path::new("config.json")).unwrap();
+        let debug = format!("{config:?}");
+
+        assert!(debug.contains("***"));
+        assert!(debug.contains("port: 1234"));
+        assert!(debug.contains(&config.base_url));
+        assert!(!debug.contains("user@database"));
+        assert!(!debug.contains(&config.s3_path));
+    }
+
+    #[test]
+    fn test_defaults() {
+        let json = r#"{
+            "base_url": "https://config.teach-rs.tweede.golf",
+            "s3_path": "bucket.teach-rs.tweede.golf",
+            "database_url": "postgresql://user@database:5432/db"
+        }"#;
+        let config: Config = DeserializeInto::deserialize(&JsonDeserializer, json).unwrap();
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.s3_path, "bucket.teach-rs.tweede.golf");
+        assert!(config.validate().is_ok());
+
+        let config: Config = DeserializeInto::deserialize(&YamlDeserializer, "port: 1234").unwrap();
+        assert_eq!(config.port, 1234);
+        assert_eq!(config.base_url, "");
+    }
+
+    #[test]
+    fn test_from_path() {
+        let path = std::env::temp_dir().join(format!("config-reader-{}.json", std::process::id()));
+        std::fs::write(&path, include_str!("../config.json")).unwrap();
+        let config = Config::from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let config = config.unwrap();
+        assert_eq!(config.port, 1234);
+        assert_eq!(config.database_url, "postgresql://user@database:5432/db");
+
+        let config = Config::from_path(Path::new("config.yml")).unwrap();
+        assert_eq!(config.base_url, "https://config.teach-rs.tweede.golf");
+    }
+
+    #[test]
+    fn test_load_layered() {
+        let dir = std::env::temp_dir();
+        let override_path = dir.join(format!("config-reader-override-{}.json", std::process::id()));
+        std::fs::write(&override_path, r#"{ "port": 4321 }"#).unwrap();
+
+        let config = Config::load_layered(&[Path::new("config.yml"), &override_path]);
+        let reversed = Config::load_layered(&[&override_path, Path::new("config.yml")]);
+        let partial = Config::load_layered(&[&override_path]);
+        std::fs::remove_file(&override_path).unwrap();
+
+        let config = config.unwrap();
+        assert_eq!(config.port, 4321);
+        assert_eq!(config.base_url, "https://config.teach-rs.tweede.golf");
+        assert_eq!(config.database_url, "postgresql://user@database:5432/db");
+
+        assert_eq!(reversed.unwrap().port, 1234);
+
+        let partial = partial.unwrap();
+        assert_eq!(partial.port, 4321);
+        assert_eq!(partial.s3_path, Config::default().s3_path);
+
+        let result = Config::load_layered(&[Path::new("config.yml"), Path::new("missing.json")]);
+        assert!(matches!(result, Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn test_validate_file() {
+        assert!(validate_file(Path::new("config.json")).is_ok());
+        assert!(validate_file(Path::new("config.yml")).is_ok());
+
+        let path = std::env::temp_dir().join(format!("config-reader-check-{}.json", std::process::id()));
+        std::fs::write(&path, "{ \"port\": 1234, ").unwrap();
+        let malformed = validate_file(&path);
+        std::fs::write(&path, include_str!("../config.json").replace("1234", "0")).unwrap();
+        let invalid = validate_file(&path);
+        std::fs::remove_file(&path).unwrap();
 
-    println!("Config was: {config:?}");
+        assert!(matches!(malformed, Err(Error::Json { .. })));
+        assert!(matches!(invalid, Err(Error::Invalid { field: "port", .. })));
+    }
+
+    #[test]
+    fn test_from_path_errors() {
+        let result = Config::from_path(Path::new("does-not-exist.json"));
+        assert!(matches!(result, Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound));
+
+        let result = Config::from_path(Path::new("config.xml"));
+        assert!(matches!(result, Err(Error::UnsupportedFormat(Some(ext))) if ext == "xml"));
+
+        let result = Config::from_path(Path::new("config"));
+        assert!(matches!(result, Err(Error::UnsupportedFormat(None))));
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("json".parse::<Format>().unwrap(), Format::Json);
+        assert_eq!("yaml".parse::<Format>().unwrap(), Format::Yaml);
+        assert_eq!("yml".parse::<Format>().unwrap(), Format::Yaml);
+        assert_eq!("toml".parse::<Format>().unwrap(), Format::Toml);
+
+        let result = "ini".parse::<Format>();
+        assert!(matches!(result, Err(Error::UnsupportedFormat(Some(format))) if format == "ini"));
+        let result = "JSON".parse::<Format>();
+        assert!(matches!(result, Err(Error::UnsupportedFormat(_))));
+
+        let config = Format::Toml
+            .deserializer()
+            .deserialize(include_str!("../config.toml"))
+            .unwrap();
+        assert_eq!(config.port, 1234);
+    }
+
+    #[test]
+    fn test_detect_and_deserialize() {
+        for contents in [
+            include_str!("../config.json"),
+            include_str!("../config.yml"),
+            include_str!("../config.toml"),
+        ] {
+            let config = detect_and_deserialize(contents).unwrap();
+            assert_eq!(config.port, 1234);
+        }
+
+        let result = detect_and_deserialize("port = [1234");
+        let Err(Error::AllFormatsFailed(errors)) = result else {
+            panic!("expected all formats to fail, got {result:?}");
+        };
+        let formats: Vec<Format> = errors.iter().map(|(format, _)| *format).collect();
+        assert_eq!(formats, [Format::Json, Format::Toml, Format::Yaml]);
+        assert!(errors[0].1.starts_with("Json"), "{}", errors[0].1);
+        assert!(errors[1].1.starts_with("Toml"), "{}", errors[1].1);
+        assert!(errors[2].1.starts_with("Yaml"), "{}", errors[2].1);
+    }
+
+    #[test]
+    fn test_dump_effective() {
+        let mut config = Config::from_path(Path::new("config.json")).unwrap();
+        let layer: PartialConfig = Format::Yaml.deserialize("port: 4321").unwrap();
+        layer.apply_to(&mut config);
+
+        let yaml = dump_effective(&config, Format::Yaml).unwrap();
+        assert!(yaml.contains("port: 4321"), "{yaml}");
+        assert!(yaml.contains("postgresql://user@database:5432/db"), "{yaml}");
+
+        for format in [Format::Json, Format::Yaml, Format::Toml] {
+            let dump = dump_effective(&config, format).unwrap();
+            let reloaded: Config = format.deserialize(&dump).unwrap();
+            assert_eq!(reloaded.port, 4321);
+            assert_eq!(reloaded.s3_path, config.s3_path);
+        }
+    }
+
+    #[test]
+    fn test_json_error_field() {
+        let json = r#"{
+            "port": "1234",
+            "base_url": "https://config.teach-rs.tweede.golf",
+            "s3_path": "bucket.teach-rs.tweede.golf",
+            "database_url": "postgresql://user@database:5432/db"
+        }"#;
+        let result = DeserializeConfig::deserialize(&JsonDeserializer, json);
+        assert!(matches!(result, Err(Error::Json { field: Some(field), .. }) if field == "port"));
+
+        let result = DeserializeConfig::deserialize(&JsonDeserializer, "{");
+        assert!(matches!(result, Err(Error::Json { field: None, .. })));
+
+        let result = DeserializeConfig::deserialize(&JsonDeserializer, &format!("{} {{", include_str!("../config.json")));
+        assert!(matches!(result, Err(Error::Json { field: None, .. })));
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let config = Config::from_reader(include_str!("../config.yml").as_bytes(), "yaml").unwrap();
+        assert_eq!(config.port, 1234);
+
+        let config = Config::from_reader(std::io::Cursor::new(include_str!("../config.json")), "json").unwrap();
+        assert_eq!(config.s3_path, "bucket.teach-rs.tweede.golf");
+
+        let result = Config::from_reader(include_str!("../config.json").as_bytes(), "xml");
+        assert!(matches!(result, Err(Error::UnsupportedFormat(Some(format))) if format == "xml"));
+    }
+
+    #[test]
+    fn test_deserialize_into() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Server {
+            host: String,
+            ports: Vec<u16>,
+        }
+
+        let expected = Server { host: "localhost".to_owned(), ports: vec![80, 443] };
+
+        let json = r#"{ "host": "localhost", "ports": [80, 443] }"#;
+        let server: Server = DeserializeInto::deserialize(&JsonDeserializer, json).unwrap();
+        assert_eq!(server, expected);
+
+        let yaml = "host: localhost\nports: [80, 443]\n";
+        let server: Server = DeserializeInto::deserialize(&YamlDeserializer, yaml).unwrap();
+        assert_eq!(server, expected);
+
+        let json = r#"{ "host": "localhost" }"#;
+        let result: Result<Server, _> = DeserializeInto::deserialize(&JsonDeserializer, json);
+        assert!(matches!(result, Err(Error::Json { .. })));
+    }
+
+    #[test]
+    fn test_load() {
+        let config: Config = load(Path::new("config.yml")).unwrap();
+        assert_eq!(config.port, 1234);
+
+        let value: serde_json::Value = load(Path::new("config.json")).unwrap();
+        assert_eq!(value["s3_path"], "bucket.teach-rs.tweede.golf");
+
+        let config: Config = load(Path::new("config.toml")).unwrap();
+        assert_eq!(config.database_url, "postgresql://user@database:5432/db");
+
+        let result = load::<Config>(Path::new("config.xml"));
+        assert!(matches!(result, Err(Error::UnsupportedFormat(Some(ext))) if ext == "xml"));
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn test_parse_urls() {
+        let config = Config::from_path(Path::new("config.json")).unwrap();
+        let urls = config.parse_urls().unwrap();
+        assert_eq!(urls.base_url.host_str(), Some("config.teach-rs.tweede.golf"));
+        assert_eq!(urls.database_url.scheme(), "postgresql");
+        assert_eq!(urls.database_url.port(), Some(5432));
+
+        let config = Config {
+            database_url: "not a url".to_owned(),
+            ..config
+        };
+        let result = config.parse_urls();
+        assert!(matches!(result, Err(Error::Url { field: "database_url", .. })));
+    }
 }
@@ -1,13 +1,21 @@
 use serde::{de::Visitor, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 /// Error creating BSN
-// TODO: update the enum to make it more descriptive
-// as there can be several reasons for a BSN to not be valid
 pub enum Error {
-    /// The BSN was invalid
-    InvalidBsn,
+    /// The BSN does not consist of 8 or 9 digits
+    WrongLength { got: usize },
+    /// The BSN contains a character that is not an ASCII digit.
+    /// `index` is the position of the character, starting at 0
+    NonDigit { index: usize, found: char },
+    /// The digits of the BSN do not pass the eleven-test
+    ChecksumFailed,
+    /// The BSN consists of only zeros, which passes the eleven-test but is never issued
+    AllZeros,
 }
 
 impl std::error::Error for Error {}
@@ -15,7 +23,12 @@ impl std::error::Error for Error {}
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::InvalidBsn => write!(f, "Invalid BSN number"),
+            Error::WrongLength { got } => write!(f, "BSN number must be 8 or 9 digits long, got {got}"),
+            Error::NonDigit { index, found } => {
+                write!(f, "BSN number contains non-digit character '{found}' at index {index}")
+            }
+            Error::ChecksumFailed => write!(f, "BSN number does not pass the eleven-test"),
+            Error::AllZeros => write!(f, "BSN number must not be all zeros"),
         }
     }
 }
@@ -24,9 +37,40 @@ impl Display for Error {
 /// personal identification number that is similar
 /// to the US Social Security Number.
 /// More info (Dutch): https://www.rvig.nl/bsn
-#[derive(Debug, PartialEq, Eq, Clone)]
+///
+/// BSNs are compared, ordered and hashed by their canonical 9-digit form,
+/// so an 8-digit BSN equals its zero-padded 9-digit counterpart. The input a BSN was
+/// created from is kept as well, e.g. for audit logs
+#[derive(Debug, Clone)]
 pub struct Bsn {
-    inner: String,
+    original: String,
+    canonical: String,
+}
+
+impl PartialEq for Bsn {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for Bsn {}
+
+impl PartialOrd for Bsn {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bsn {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.canonical().cmp(other.canonical())
+    }
+}
+
+impl Hash for Bsn {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state);
+    }
 }
 
 impl Bsn {
@@ -35,41 +79,305 @@ impl Bsn {
     pub fn try_from_string<B: ToString>(bsn: B) -> Result<Self, Error> {
         let bsn_string = bsn.to_string();
 
-        if Self::validate(bsn_string.as_str()).is_err() {
-            return Err(Error::InvalidBsn);
+        Self::validate(bsn_string.as_str())?;
+
+        Ok(Self::from_validated(bsn_string))
+    }
+
+    /// Create a BSN from a string that passed [`Bsn::validate`]
+    fn from_validated(bsn: String) -> Self {
+        Self {
+            canonical: format!("{bsn:0>9}"),
+            original: bsn,
+        }
+    }
+
+    /// Replace the original input of this BSN, for constructors that
+    /// extract the digits from a formatted input
+    fn with_original(mut self, original: &str) -> Self {
+        self.original = original.to_owned();
+        self
+    }
+
+    /// The digits this BSN was created from, without any whitespace or separators.
+    /// Unlike the canonical form, an 8-digit BSN is not zero-padded
+    fn digits(&self) -> &str {
+        // The original input has 8 or 9 digits, or 10 with the leading zero `try_from_loose` strips
+        let len = self.original.bytes().filter(u8::is_ascii_digit).count().min(9);
+        &self.canonical[9 - len..]
+    }
+
+    /// Try to create a new BSN from a sequence of characters, such as
+    /// a part of a stream being parsed
+    pub fn try_from_chars<I: IntoIterator<Item = char>>(chars: I) -> Result<Self, Error> {
+        Self::try_from_string(chars.into_iter().collect::<String>())
+    }
+
+    /// Try to create a new BSN from a leniently formatted input.
+    /// Surrounding whitespace is ignored, as is a single leading zero
+    /// padding the number to 10 characters, e.g. `" 0999998456"`.
+    pub fn try_from_loose(input: &str) -> Result<Self, Error> {
+        let trimmed = input.trim();
+
+        let digits = if trimmed.len() == 10 {
+            trimmed.strip_prefix('0').unwrap_or(trimmed)
+        } else {
+            trimmed
+        };
+
+        Ok(Self::try_from_string(digits)?.with_original(input))
+    }
+
+    /// Try to create a new BSN from any of the common notations: plain digits
+    /// (`"999998456"`), dotted (`"9999.98.456"`) or space-separated (`"9999 98 456"`).
+    /// The separators are removed before validating, but may not be mixed
+    pub fn parse_any(input: &str) -> Result<Self, Error> {
+        let trimmed = input.trim();
+        let separator = trimmed.chars().find(|c| !c.is_ascii_digit());
+
+        let mut digits = String::with_capacity(trimmed.len());
+        let mut chars = trimmed.chars().enumerate().peekable();
+        while let Some((index, char)) = chars.next() {
+            if char.is_ascii_digit() {
+                digits.push(char);
+                continue;
+            }
+
+            // Only a single kind of separator is allowed, and only between digits
+            let is_separator = Some(char) == separator && matches!(char, '.' | ' ');
+            let between_digits = index > 0 && chars.peek().is_some_and(|(_, next)| next.is_ascii_digit());
+            if !is_separator || !between_digits {
+                return Err(Error::NonDigit { index, found: char });
+            }
         }
 
-        Ok(Self {
-            inner: bsn_string,
+        Ok(Self::try_from_string(digits)?.with_original(input))
+    }
+
+    /// A stable pseudonym for this BSN: the hex-encoded SHA-256 hash of `salt`
+    /// followed by the canonical BSN digits. The same BSN and salt always yield the same token.
+    #[cfg(feature = "sha2")]
+    pub fn token(&self, salt: &[u8]) -> String {
+        use sha2::{Digest, Sha256};
+        use std::fmt::Write;
+
+        let hash = Sha256::new()
+            .chain_update(salt)
+            .chain_update(self.canonical().as_bytes())
+            .finalize();
+
+        hash.iter().fold(String::with_capacity(64), |mut token, byte| {
+            // Writing to a `String` cannot fail
+            write!(token, "{byte:02x}").unwrap();
+            token
         })
     }
 
+    /// Check whether the passed input represents this BSN, ignoring any
+    /// non-digit characters such as the separators in `"9999.98.456"`
+    pub fn matches_formatted(&self, input: &str) -> bool {
+        let digits: String = input.chars().filter(char::is_ascii_digit).collect();
+
+        (digits.len() == 8 || digits.len() == 9) && format!("{digits:0>9}") == self.canonical
+    }
+
+    /// Whether this BSN is in the `999` range reserved for testing.
+    /// An 8-digit BSN is implicitly prefixed with a zero, so it never is
+    pub fn is_test(&self) -> bool {
+        self.canonical.starts_with("999")
+    }
+
+    /// The canonical 9-digit form of this BSN, zero-padding an 8-digit BSN
+    pub fn canonical(&self) -> &str {
+        &self.canonical
+    }
+
+    /// The exact input this BSN was created from, including any
+    /// whitespace or separators accepted by the constructor
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    /// The canonical digits of this BSN, left-padded with zeros to at least `width`
+    /// characters. A `width` of 9 or less returns the digits unchanged
+    pub fn padded(&self, width: usize) -> String {
+        format!("{:0>width$}", self.canonical)
+    }
+
     /// Check whether the passed string represents a valid BSN.
     //  Returns `Err` if the passed string does not represent a valid BSN
     pub fn validate(bsn: &str) -> Result<(), Error> {
-        let bsn_string_len = bsn.len();
+        let result = Self::checksum(bsn)?;
+
+        if result % 11 != 0 {
+            Err(Error::ChecksumFailed)?;
+        }
 
-        if bsn_string_len != 8 && bsn_string_len != 9 {
-            Err(Error::InvalidBsn)?;
+        if is_all_zeros(bsn.as_bytes()) {
+            Err(Error::AllZeros)?;
         }
 
-        let mut result: i32 = 0;
-        let mut multiplier: i32 = 9;
+        Ok(())
+    }
 
-        for char in bsn.chars() {
-            result += ((char as i32) - '0' as i32) * multiplier;
-            multiplier -= 1;
-            if multiplier == 1 {
-                multiplier = -1;
-            }
+    /// Like [`Bsn::validate`], but returns the canonical 9-digit form of the
+    /// passed string on success, zero-padding an 8-digit BSN
+    pub fn validate_normalized(bsn: &str) -> Result<String, Error> {
+        Self::validate(bsn)?;
+        Ok(format!("{bsn:0>9}"))
+    }
+
+    /// Check whether the passed bytes represent a valid BSN, without requiring
+    /// them to be valid UTF-8 first.
+    //  Returns `Err` if the passed bytes do not represent a valid BSN
+    pub fn validate_bytes(bsn: &[u8]) -> Result<(), Error> {
+        if let Some(index) = bsn.iter().position(|byte| !byte.is_ascii_digit()) {
+            Err(Error::NonDigit {
+                index,
+                found: char::from(bsn[index]),
+            })?;
         }
 
-        if result % 11 != 0 {
-            Err(Error::InvalidBsn)?;
+        if weighted_sum(bsn)? % 11 != 0 {
+            Err(Error::ChecksumFailed)?;
+        }
+
+        if is_all_zeros(bsn) {
+            Err(Error::AllZeros)?;
         }
 
         Ok(())
     }
+
+    /// Explain in a human-readable way why the passed string is not a valid BSN,
+    /// or return `"valid"` if it is. Character positions start at 1.
+    pub fn explain(bsn: &str) -> String {
+        let bsn_len = bsn.len();
+
+        if bsn_len != 8 && bsn_len != 9 {
+            return format!("length is {bsn_len}, must be 8 or 9");
+        }
+
+        if let Some((index, char)) = bsn.chars().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+            return format!("character '{char}' at position {} is not a digit", index + 1);
+        }
+
+        match Self::checksum(bsn) {
+            Ok(result) if result % 11 != 0 => format!("checksum {result} is not divisible by 11"),
+            Ok(_) if is_all_zeros(bsn.as_bytes()) => Error::AllZeros.to_string(),
+            Ok(_) => "valid".to_owned(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    /// A set of known-valid BSNs, for seeding tests with
+    pub fn sample_valid() -> &'static [&'static str] {
+        static SAMPLES: OnceLock<Vec<&'static str>> = OnceLock::new();
+
+        SAMPLES.get_or_init(|| {
+            include_str!("../valid_bsns.in")
+                .lines()
+                .map(str::trim)
+                .filter(|bsn| !bsn.is_empty())
+                .collect()
+        })
+    }
+
+    /// Compute the weighted sum of the digits of the passed string, which
+    /// is the value checked against `% 11` by [`Bsn::validate`].
+    /// Returns `Err` if the passed string does not consist of 8 or 9 digits
+    pub fn checksum(bsn: &str) -> Result<i32, Error> {
+        if let Some((index, found)) = bsn.chars().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+            Err(Error::NonDigit { index, found })?;
+        }
+
+        weighted_sum(bsn.as_bytes())
+    }
+}
+
+/// Whether the ASCII digits in `digits` are all zeros, which is never a real BSN
+fn is_all_zeros(digits: &[u8]) -> bool {
+    digits.iter().all(|&digit| digit == b'0')
+}
+
+/// Weigh the ASCII digits in `digits` by 9, 8, 7, 6, 5, 4, 3, 2 and -1 and sum them.
+/// An 8-digit BSN is weighed as if it were prefixed with a zero
+fn weighted_sum(digits: &[u8]) -> Result<i32, Error> {
+    const WEIGHTS: [i32; 9] = [9, 8, 7, 6, 5, 4, 3, 2, -1];
+
+    let len = digits.len();
+    if len != 8 && len != 9 {
+        Err(Error::WrongLength { got: len })?;
+    }
+
+    Ok(digits
+        .iter()
+        .zip(&WEIGHTS[9 - len..])
+        .map(|(digit, weight)| i32::from(digit - b'0') * weight)
+        .sum())
+}
+
+impl TryFrom<u64> for Bsn {
+    type Error = Error;
+
+    /// Try to create a BSN from an integer, which is zero-padded to 9 digits
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        if value > 999_999_999 {
+            return Err(Error::WrongLength {
+                got: value.ilog10() as usize + 1,
+            });
+        }
+
+        Self::try_from_string(format!("{value:09}"))
+    }
+}
+
+impl TryFrom<u32> for Bsn {
+    type Error = Error;
+
+    /// Try to create a BSN from an integer, which is zero-padded to 9 digits
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Self::try_from(u64::from(value))
+    }
+}
+
+/// A map keyed by valid BSNs, which can be queried by raw strings
+#[derive(Debug, Clone)]
+pub struct BsnMap<V>(HashMap<Bsn, V>);
+
+impl<V> BsnMap<V> {
+    /// Create an empty `BsnMap`
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Insert a value for the passed BSN, returning the previous value if any
+    pub fn insert(&mut self, bsn: Bsn, value: V) -> Option<V> {
+        self.0.insert(bsn, value)
+    }
+
+    /// Get the value for the passed string, if it represents a valid BSN
+    /// that is present in the map
+    pub fn get(&self, bsn: &str) -> Option<&V> {
+        let bsn = Bsn::try_from_string(bsn).ok()?;
+        self.0.get(&bsn)
+    }
+
+    /// The number of entries in the map
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the map contains no entries
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<V> Default for BsnMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Serialize for Bsn {
@@ -77,7 +385,7 @@ impl Serialize for Bsn {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(self.inner.as_str())
+        serializer.serialize_str(self.digits())
     }
 }
 
@@ -108,25 +416,63 @@ impl<'de> Deserialize<'de> for Bsn {
             where
                 E: serde::de::Error,
             {
-                let str_len = str.len();
-
-                if Bsn::validate(str.as_str()).is_err() {
-                    return Err(serde::de::Error::invalid_length(str_len, &self));
+                match Bsn::validate(str.as_str()) {
+                    Ok(()) => {}
+                    Err(Error::WrongLength { got }) => return Err(E::invalid_length(got, &self)),
+                    Err(e) => return Err(E::custom(e)),
                 }
 
-                Ok(Bsn {
-                    inner: String::from(str)
-                })
+                Ok(Bsn::from_validated(str))
             }
         }
 
-        Ok(deserializer.deserialize_any(BsnVisitor {})?)
+        deserializer.deserialize_str(BsnVisitor {})
     }
 }
 
+/// Deserialize a comma-separated string of BSNs, e.g. `"999998456, 010082426"`,
+/// into a `Vec<Bsn>`. Meant to be used with `#[serde(deserialize_with = "deserialize_bsn_list")]`.
+/// Fails on the first invalid BSN, reporting its index in the list.
+pub fn deserialize_bsn_list<'de, D>(deserializer: D) -> Result<Vec<Bsn>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let list = String::deserialize(deserializer)?;
+
+    if list.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    list.split(',')
+        .enumerate()
+        .map(|(index, bsn)| {
+            Bsn::try_from_string(bsn.trim())
+                .map_err(|e| serde::de::Error::custom(format!("BSN at index {index} is invalid: {e}")))
+        })
+        .collect()
+}
+
+/// Remove duplicate BSNs from `bsns`, keeping the first occurrence of each.
+/// BSNs are compared by their canonical form, so `10082426` and `010082426` are duplicates
+pub fn dedup_bsns(bsns: &mut Vec<Bsn>) {
+    let mut seen = HashSet::with_capacity(bsns.len());
+    bsns.retain(|bsn| seen.insert(bsn.canonical().to_owned()));
+}
+
+/// Serialize a `Bsn` as an integer instead of a string, e.g. `999998456` rather than `"999998456"`.
+/// Meant to be used with `#[serde(serialize_with = "serialize_as_number")]`.
+/// Note that leading zeros are lost: `010082426` is emitted as `10082426`.
+pub fn serialize_as_number<S>(bsn: &Bsn, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let number = bsn.canonical().parse::<u64>().map_err(serde::ser::Error::custom)?;
+    serializer.serialize_u64(number)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::Bsn;
+    use crate::{dedup_bsns, deserialize_bsn_list, serialize_as_number, Bsn, BsnMap, Error};
 
     #[test]
     fn test_validation() {
@@ -137,6 +483,76 @@ mod tests {
         bsns.for_each(|bsn| assert!(Bsn::validate(bsn).is_err(), "BSN {bsn} invalid, but passed validation"));
     }
 
+    #[test]
+    fn test_validation_errors() {
+        assert_eq!(Bsn::validate("9999984a6"), Err(Error::NonDigit { index: 7, found: 'a' }));
+        assert_eq!(Bsn::validate("99999845é"), Err(Error::NonDigit { index: 8, found: 'é' }));
+        assert_eq!(Bsn::validate(""), Err(Error::WrongLength { got: 0 }));
+        assert_eq!(Bsn::validate("9999984560"), Err(Error::WrongLength { got: 10 }));
+        assert_eq!(Bsn::validate("999998457"), Err(Error::ChecksumFailed));
+
+        assert_eq!(
+            Error::NonDigit { index: 7, found: 'a' }.to_string(),
+            "BSN number contains non-digit character 'a' at index 7"
+        );
+    }
+
+    #[test]
+    fn test_validation_all_zeros() {
+        assert_eq!(Bsn::checksum("000000000").unwrap(), 0);
+        assert_eq!(Bsn::validate("000000000"), Err(Error::AllZeros));
+        assert_eq!(Bsn::validate("00000000"), Err(Error::AllZeros));
+        assert_eq!(Bsn::validate_bytes(b"000000000"), Err(Error::AllZeros));
+        assert_eq!(Bsn::try_from(0u32), Err(Error::AllZeros));
+        assert_eq!(Bsn::explain("000000000"), "BSN number must not be all zeros");
+
+        assert_eq!(Bsn::validate("000000012"), Ok(()));
+        assert_eq!(Bsn::validate("999998456"), Ok(()));
+    }
+
+    #[test]
+    fn test_validation_eight_digits() {
+        // An 8-digit BSN is valid exactly if its zero-padded 9-digit form is
+        for bsn in ["10082426", "10000094"] {
+            assert_eq!(Bsn::validate(bsn), Ok(()));
+            assert_eq!(Bsn::validate(&format!("0{bsn}")), Ok(()));
+            assert_eq!(Bsn::checksum(bsn).unwrap(), Bsn::checksum(&format!("0{bsn}")).unwrap());
+        }
+        assert_eq!(Bsn::validate("10000001"), Err(Error::ChecksumFailed));
+        assert_eq!(Bsn::validate("010000001"), Err(Error::ChecksumFailed));
+
+        assert_eq!(Bsn::validate_bytes(b"10082426"), Ok(()));
+        assert_eq!(Bsn::validate_bytes(b"10000001"), Err(Error::ChecksumFailed));
+        assert_eq!(Bsn::validate_bytes(b"1234567"), Err(Error::WrongLength { got: 7 }));
+    }
+
+    #[test]
+    fn test_validate_normalized() {
+        assert_eq!(Bsn::validate_normalized("10082426"), Ok(String::from("010082426")));
+        assert_eq!(Bsn::validate_normalized("999998456"), Ok(String::from("999998456")));
+        assert_eq!(Bsn::validate_normalized("999998457"), Err(Error::ChecksumFailed));
+        assert_eq!(Bsn::validate_normalized("1234567"), Err(Error::WrongLength { got: 7 }));
+    }
+
+    #[test]
+    fn test_sample_valid() {
+        let samples = Bsn::sample_valid();
+        assert!(!samples.is_empty());
+        samples.iter().for_each(|bsn| assert!(Bsn::validate(bsn).is_ok(), "sample BSN {bsn} did not pass validation"));
+    }
+
+    #[test]
+    fn test_validation_bytes() {
+        let bsns = include_str!("../valid_bsns.in").lines();
+        bsns.for_each(|bsn| assert!(Bsn::validate_bytes(bsn.as_bytes()).is_ok(), "BSN {bsn} is valid, but did not pass validation"));
+
+        let bsns = include_str!("../invalid_bsns.in").lines();
+        bsns.for_each(|bsn| assert!(Bsn::validate_bytes(bsn.as_bytes()).is_err(), "BSN {bsn} invalid, but passed validation"));
+
+        assert_eq!(Bsn::validate_bytes(b"99999845a"), Err(Error::NonDigit { index: 8, found: 'a' }));
+        assert!(matches!(Bsn::validate_bytes(&[0xff; 9]), Err(Error::NonDigit { index: 0, .. })));
+    }
+
     #[test]
     fn test_serde() {
         let json = serde_json::to_string(&Bsn::try_from_string("999998456").unwrap()).unwrap();
@@ -144,6 +560,256 @@ mod tests {
         let bsn: Bsn = serde_json::from_str("\"999998456\"").unwrap();
         assert_eq!(bsn, Bsn::try_from_string("999998456".to_string()).unwrap());
 
-        serde_json::from_str::<Bsn>("\"1112223333\"").unwrap_err();
+        let error = serde_json::from_str::<Bsn>("\"1112223333\"").unwrap_err();
+        assert!(error.to_string().contains("invalid length 10"), "{error}");
+        let error = serde_json::from_str::<Bsn>("\"9999984a6\"").unwrap_err();
+        assert!(error.to_string().contains("non-digit character 'a' at index 7"), "{error}");
+        let error = serde_json::from_str::<Bsn>("\"999998457\"").unwrap_err();
+        assert!(error.to_string().contains("eleven-test"), "{error}");
+    }
+
+    #[test]
+    fn test_serde_non_self_describing() {
+        let bsn = Bsn::try_from_string("999998456").unwrap();
+        let bytes = bincode::serialize(&bsn).unwrap();
+        assert_eq!(bincode::deserialize::<Bsn>(&bytes).unwrap(), bsn);
+
+        let bytes = bincode::serialize("1112223333").unwrap();
+        bincode::deserialize::<Bsn>(&bytes).unwrap_err();
+    }
+
+    #[test]
+    fn test_deserialize_bsn_list() {
+        let parse = |json: &str| deserialize_bsn_list(&mut serde_json::Deserializer::from_str(json));
+
+        let bsns = parse("\"999998456, 999998456\"").unwrap();
+        assert_eq!(bsns, vec![Bsn::try_from_string("999998456").unwrap(); 2]);
+
+        assert!(parse("\"\"").unwrap().is_empty());
+
+        let error = parse("\"999998456, 123456789, 999998456\"").unwrap_err();
+        assert!(error.to_string().contains("BSN at index 1 is invalid"), "{error}");
+    }
+
+    #[test]
+    fn test_serde_leading_zeros() {
+        for input in ["010082426", "000000012"] {
+            let json = serde_json::to_string(&Bsn::try_from_string(input).unwrap()).unwrap();
+            assert_eq!(json, format!("\"{input}\""));
+
+            let bsn: Bsn = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&bsn).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn test_dedup_bsns() {
+        let mut bsns: Vec<Bsn> = ["10082426", "999998456", "010082426", "999998456"]
+            .into_iter()
+            .map(|bsn| Bsn::try_from_string(bsn).unwrap())
+            .collect();
+        dedup_bsns(&mut bsns);
+
+        assert_eq!(bsns.len(), 2);
+        assert_eq!(bsns[0].original(), "10082426");
+        assert_eq!(bsns[1].canonical(), "999998456");
+    }
+
+    #[test]
+    fn test_serialize_as_number() {
+        let to_json = |input: &str| {
+            let mut json = Vec::new();
+            serialize_as_number(&Bsn::try_from_string(input).unwrap(), &mut serde_json::Serializer::new(&mut json)).unwrap();
+            String::from_utf8(json).unwrap()
+        };
+
+        assert_eq!(to_json("999998456"), "999998456");
+        // Leading zeros do not survive as a number
+        assert_eq!(to_json("010082426"), "10082426");
+    }
+
+    #[test]
+    fn test_explain() {
+        assert_eq!(Bsn::explain("999998456"), "valid");
+        assert_eq!(Bsn::explain("1234567"), "length is 7, must be 8 or 9");
+        assert_eq!(Bsn::explain("99x998456"), "character 'x' at position 3 is not a digit");
+        assert_eq!(Bsn::explain("123456789"), "checksum 147 is not divisible by 11");
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(Bsn::checksum("999998456").unwrap() % 11, 0);
+        assert_eq!(Bsn::checksum("123456789").unwrap(), 147);
+        assert!(Bsn::checksum("1234567890").is_err());
+    }
+
+    #[test]
+    fn test_try_from_chars() {
+        let expected = Bsn::try_from_string("999998456").unwrap();
+        assert_eq!(Bsn::try_from_chars("999998456".chars()).unwrap(), expected);
+        assert_eq!(Bsn::try_from_chars("x999998456".chars().skip(1)).unwrap(), expected);
+
+        assert_eq!(Bsn::try_from_chars("99999845a".chars()), Err(Error::NonDigit { index: 8, found: 'a' }));
+        assert_eq!(Bsn::try_from_chars(['1', '2']), Err(Error::WrongLength { got: 2 }));
+    }
+
+    #[test]
+    fn test_try_from_loose() {
+        let expected = Bsn::try_from_string("999998456").unwrap();
+
+        assert_eq!(Bsn::try_from_loose("  999998456\t").unwrap(), expected);
+        assert_eq!(Bsn::try_from_loose("0999998456").unwrap(), expected);
+        assert_eq!(Bsn::try_from_loose(" 0999998456").unwrap(), expected);
+
+        assert!(Bsn::try_from_loose("00999998456").is_err());
+        assert!(Bsn::try_from_loose("1999998456").is_err());
+        assert!(Bsn::try_from_string(" 999998456").is_err());
+    }
+
+    #[test]
+    fn test_parse_any() {
+        let expected = Bsn::try_from_string("999998456").unwrap();
+
+        assert_eq!(Bsn::parse_any("999998456").unwrap(), expected);
+        assert_eq!(Bsn::parse_any("9999.98.456").unwrap(), expected);
+        assert_eq!(Bsn::parse_any("9999 98 456").unwrap(), expected);
+        assert_eq!(Bsn::parse_any(" 999 998 456 ").unwrap(), expected);
+
+        assert_eq!(Bsn::parse_any("9999.98 456"), Err(Error::NonDigit { index: 7, found: ' ' }));
+        assert_eq!(Bsn::parse_any("9999..98456"), Err(Error::NonDigit { index: 4, found: '.' }));
+        assert_eq!(Bsn::parse_any(".999998456"), Err(Error::NonDigit { index: 0, found: '.' }));
+        assert_eq!(Bsn::parse_any("999998456."), Err(Error::NonDigit { index: 9, found: '.' }));
+        assert_eq!(Bsn::parse_any("9999-98-456"), Err(Error::NonDigit { index: 4, found: '-' }));
+        assert_eq!(Bsn::parse_any("9999.98.457"), Err(Error::ChecksumFailed));
+        assert_eq!(Bsn::parse_any("99.99"), Err(Error::WrongLength { got: 4 }));
+        assert_eq!(Bsn::parse_any(""), Err(Error::WrongLength { got: 0 }));
+    }
+
+    #[test]
+    fn test_matches_formatted() {
+        let bsn = Bsn::try_from_string("999998456").unwrap();
+
+        assert!(bsn.matches_formatted("999998456"));
+        assert!(bsn.matches_formatted("9999.98.456"));
+        assert!(bsn.matches_formatted("9999 98 456"));
+        assert!(!bsn.matches_formatted("9999.98.457"));
+        assert!(!bsn.matches_formatted("9999.98.4560"));
+
+        let bsn = Bsn::try_from_string("10082426").unwrap();
+        assert!(bsn.matches_formatted("0100.82.426"));
+        assert!(bsn.matches_formatted("1008 24 26"));
+    }
+
+    #[test]
+    fn test_is_test() {
+        assert!(Bsn::try_from_string("999998456").unwrap().is_test());
+        assert!(!Bsn::try_from_string("010082426").unwrap().is_test());
+        assert!(!Bsn::try_from_string("000000012").unwrap().is_test());
+    }
+
+    #[test]
+    fn test_canonical() {
+        let bsn = Bsn::try_from_string("999998456").unwrap();
+        assert_eq!(bsn.canonical(), "999998456");
+
+        let bsn = Bsn::try_from_string("10000094").unwrap();
+        assert_eq!(bsn.canonical(), "010000094");
+        assert_eq!(bsn.canonical(), Bsn::try_from_string("010000094").unwrap().canonical());
+    }
+
+    #[test]
+    fn test_padded() {
+        let bsn = Bsn::try_from_string("999998456").unwrap();
+        assert_eq!(bsn.padded(11), "00999998456");
+        assert_eq!(bsn.padded(9), "999998456");
+        assert_eq!(bsn.padded(0), "999998456");
+        assert_eq!(Bsn::try_from_string("10082426").unwrap().padded(10), "0010082426");
+    }
+
+    #[test]
+    fn test_original() {
+        let bsn = Bsn::try_from_string("10082426").unwrap();
+        assert_eq!(bsn.original(), "10082426");
+        assert_eq!(bsn.canonical(), "010082426");
+        assert_eq!(bsn, Bsn::try_from_string("010082426").unwrap());
+        assert_eq!(serde_json::to_string(&bsn).unwrap(), "\"10082426\"");
+
+        let bsn = Bsn::parse_any(" 9999.98.456 ").unwrap();
+        assert_eq!(bsn.original(), " 9999.98.456 ");
+        assert_eq!(bsn.canonical(), "999998456");
+
+        let bsn = Bsn::try_from_loose("0999998456").unwrap();
+        assert_eq!(bsn.original(), "0999998456");
+        assert_eq!(bsn.canonical(), "999998456");
+        assert_eq!(serde_json::to_string(&bsn).unwrap(), "\"999998456\"");
+
+        let bsn = Bsn::parse_any("1008.24.26").unwrap();
+        assert_eq!(serde_json::to_string(&bsn).unwrap(), "\"10082426\"");
+    }
+
+    #[test]
+    fn test_canonical_equality() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |bsn: &Bsn| {
+            let mut hasher = DefaultHasher::new();
+            bsn.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let short = Bsn::try_from_string("10082426").unwrap();
+        let long = Bsn::try_from_string("010082426").unwrap();
+        assert_eq!(short, long);
+        assert_eq!(short.cmp(&long), std::cmp::Ordering::Equal);
+        assert_eq!(hash(&short), hash(&long));
+
+        let other = Bsn::try_from_string("999998456").unwrap();
+        assert_ne!(short, other);
+        assert!(short < other);
+        assert!(Bsn::try_from_string("000000012").unwrap() < short);
+
+        let mut map = BsnMap::new();
+        map.insert(long, "Piet");
+        assert_eq!(map.get("10082426"), Some(&"Piet"));
+    }
+
+    #[test]
+    fn test_try_from_integer() {
+        assert_eq!(Bsn::try_from(999_998_456u32).unwrap(), Bsn::try_from_string("999998456").unwrap());
+        assert_eq!(Bsn::try_from(10_082_426u64).unwrap(), Bsn::try_from_string("010082426").unwrap());
+        assert_eq!(Bsn::try_from(12u32).unwrap(), Bsn::try_from_string("000000012").unwrap());
+
+        assert_eq!(Bsn::try_from(1_112_223_333u64), Err(Error::WrongLength { got: 10 }));
+        assert_eq!(Bsn::try_from(4_294_967_295u32), Err(Error::WrongLength { got: 10 }));
+        assert_eq!(Bsn::try_from(123_456_789u32), Err(Error::ChecksumFailed));
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_token() {
+        let bsn = Bsn::try_from_string("999998456").unwrap();
+        let token = bsn.token(b"salt");
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(bsn.token(b"salt"), token);
+        assert_ne!(bsn.token(b"pepper"), token);
+
+        let other = Bsn::try_from_string("010082426").unwrap();
+        assert_ne!(other.token(b"salt"), token);
+        assert_eq!(Bsn::try_from_string("10082426").unwrap().token(b"salt"), other.token(b"salt"));
+    }
+
+    #[test]
+    fn test_bsn_map() {
+        let mut map = BsnMap::new();
+        assert!(map.is_empty());
+
+        map.insert(Bsn::try_from_string("999998456").unwrap(), "Jan");
+        assert_eq!(map.len(), 1);
+
+        assert_eq!(map.get("999998456"), Some(&"Jan"));
+        assert_eq!(map.get("000000012"), None);
+        assert_eq!(map.get("hello"), None);
     }
 }
@@ -1,14 +1,16 @@
-use std::ops::Add;
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
 
 /// Very naive implementation of FizzBuzz
 pub fn fizz_buzz(i: u32) -> String {
-    if i % 3 == 0 {
-        if i % 5 == 0 {
+    if i.is_multiple_of(3) {
+        if i.is_multiple_of(5) {
             "FizzBuzz".to_owned()
         } else {
             "Fizz".to_owned()
         }
-    } else if i % 5 == 0 {
+    } else if i.is_multiple_of(5) {
         "Buzz".to_owned()
     } else {
         format!("{i}")
@@ -16,24 +18,131 @@ pub fn fizz_buzz(i: u32) -> String {
 }
 
 pub fn fast_fizz_buzz(i: u32) -> String {
-    if i % 3 == 0 {
+    if i.is_multiple_of(3) {
         "Fizz".to_owned()
-    } else if i % 5 == 0 {
+    } else if i.is_multiple_of(5) {
         "Buzz".to_owned()
-    } else if i % 15 == 0 {
+    } else if i.is_multiple_of(15) {
         "FizzBuzz".to_owned()
     } else {
         i.to_string()
     }
 }
 
+/// The FizzBuzz classification of a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FizzBuzz {
+    Fizz,
+    Buzz,
+    FizzBuzz,
+    Number(u32),
+}
+
+impl FizzBuzz {
+    /// The text for this classification. Only the `Number` case allocates,
+    /// the words are borrowed from static strings
+    pub fn to_cow(self) -> Cow<'static, str> {
+        match self {
+            FizzBuzz::Fizz => Cow::Borrowed("Fizz"),
+            FizzBuzz::Buzz => Cow::Borrowed("Buzz"),
+            FizzBuzz::FizzBuzz => Cow::Borrowed("FizzBuzz"),
+            FizzBuzz::Number(i) => Cow::Owned(i.to_string()),
+        }
+    }
+}
+
+impl From<FizzBuzz> for Cow<'static, str> {
+    fn from(value: FizzBuzz) -> Self {
+        value.to_cow()
+    }
+}
+
+impl Display for FizzBuzz {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FizzBuzz::Number(i) => write!(f, "{i}"),
+            word => f.write_str(&word.to_cow()),
+        }
+    }
+}
+
+/// Classify a number according to the FizzBuzz rules, without allocating
+pub fn classify(i: u32) -> FizzBuzz {
+    match (i.is_multiple_of(3), i.is_multiple_of(5)) {
+        (true, true) => FizzBuzz::FizzBuzz,
+        (true, false) => FizzBuzz::Fizz,
+        (false, true) => FizzBuzz::Buzz,
+        (false, false) => FizzBuzz::Number(i),
+    }
+}
+
+/// Clear `out` and fill it with the classification of each number in `range`,
+/// reusing the capacity of `out`
+pub fn classify_range(range: RangeInclusive<u32>, out: &mut Vec<FizzBuzz>) {
+    out.clear();
+    out.extend(range.map(classify));
+}
+
+/// Iterate over the FizzBuzz words for the numbers in `range`, skipping the plain numbers
+pub fn words_only(range: RangeInclusive<u32>) -> impl Iterator<Item = &'static str> {
+    range.map(classify).filter_map(|class| match class {
+        FizzBuzz::Fizz => Some("Fizz"),
+        FizzBuzz::Buzz => Some("Buzz"),
+        FizzBuzz::FizzBuzz => Some("FizzBuzz"),
+        FizzBuzz::Number(_) => None,
+    })
+}
+
+/// FizzBuzz, but the numbers that are neither Fizz nor Buzz are written as Roman numerals.
+/// Roman numerals only go up to 3999, so larger numbers are written as usual
+pub fn fizz_buzz_roman(i: u32) -> String {
+    match classify(i) {
+        FizzBuzz::Number(i) => to_roman(i).unwrap_or_else(|| i.to_string()),
+        word => word.to_string(),
+    }
+}
+
+/// Write `i` as a Roman numeral, or `None` if it is outside `1..=3999`
+fn to_roman(mut i: u32) -> Option<String> {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    if !(1..=3999).contains(&i) {
+        return None;
+    }
+
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while i >= value {
+            roman.push_str(numeral);
+            i -= value;
+        }
+    }
+    Some(roman)
+}
+
 // TODO Write a unit test, using the contents of `fizzbuzz.out` file
 // to compare.
 // You can use the `include_str!()` macro to include file
 // contents as `&str` in your artifact.
 #[cfg(test)]
 mod tests {
-    use crate::fizz_buzz;
+    use std::borrow::Cow;
+
+    use crate::{classify, classify_range, fizz_buzz, fizz_buzz_roman, to_roman, words_only, FizzBuzz};
 
     #[test]
     fn test_fizz_buzz() {
@@ -43,4 +152,61 @@ mod tests {
             assert_eq!(line, fizz_buzz(index as u32 + 1))
         }
     }
+
+    #[test]
+    fn test_classify_range() {
+        let mut out = Vec::with_capacity(15);
+        classify_range(1..=15, &mut out);
+        assert_eq!(out.len(), 15);
+        assert_eq!(out[0], FizzBuzz::Number(1));
+        assert_eq!(out[2], FizzBuzz::Fizz);
+        assert_eq!(out[4], FizzBuzz::Buzz);
+        assert_eq!(out[14], FizzBuzz::FizzBuzz);
+
+        let capacity = out.capacity();
+        let ptr = out.as_ptr();
+        classify_range(3..=5, &mut out);
+        assert_eq!(out, vec![FizzBuzz::Fizz, FizzBuzz::Number(4), FizzBuzz::Buzz]);
+        assert_eq!(out.capacity(), capacity);
+        assert_eq!(out.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_classify_text() {
+        for i in 1..=100 {
+            let text = classify(i).to_cow();
+            assert_eq!(text.as_ref(), fizz_buzz(i));
+            assert_eq!(classify(i).to_string(), fizz_buzz(i));
+        }
+
+        assert!(matches!(classify(3).to_cow(), Cow::Borrowed("Fizz")));
+        assert!(matches!(classify(5).to_cow(), Cow::Borrowed("Buzz")));
+        assert!(matches!(Cow::from(classify(15)), Cow::Borrowed("FizzBuzz")));
+        assert!(matches!(classify(7).to_cow(), Cow::Owned(s) if s == "7"));
+    }
+
+    #[test]
+    fn test_words_only() {
+        let words: Vec<&str> = words_only(1..=15).collect();
+        assert_eq!(words, ["Fizz", "Buzz", "Fizz", "Fizz", "Buzz", "Fizz", "FizzBuzz"]);
+        assert_eq!(words.iter().filter(|&&word| word == "Fizz").count(), 4);
+
+        assert_eq!(words_only(1..=100).count(), 47);
+        assert_eq!(words_only(1..=2).next(), None);
+    }
+
+    #[test]
+    fn test_fizz_buzz_roman() {
+        assert_eq!(fizz_buzz_roman(3), "Fizz");
+        assert_eq!(fizz_buzz_roman(10), "Buzz");
+        assert_eq!(fizz_buzz_roman(30), "FizzBuzz");
+        assert_eq!(fizz_buzz_roman(1), "I");
+        assert_eq!(fizz_buzz_roman(4), "IV");
+        assert_eq!(fizz_buzz_roman(14), "XIV");
+        assert_eq!(fizz_buzz_roman(4001), "4001");
+
+        assert_eq!(to_roman(1994).as_deref(), Some("MCMXCIV"));
+        assert_eq!(to_roman(3999).as_deref(), Some("MMMCMXCIX"));
+        assert_eq!(to_roman(0), None);
+    }
 }